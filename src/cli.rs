@@ -0,0 +1,569 @@
+// SPDX-FileCopyrightText: 2026 Manuel Quarneti <mq1@ik.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::installer;
+use crate::util::{self, Arch, Channel, InstallOptions, Os, ProgressEvent, Scope};
+use anyhow::{Result, anyhow, bail};
+use iced::futures::executor::block_on;
+use serde::{Serialize, Serializer};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{ops::ControlFlow, path::PathBuf, process::ExitCode};
+use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+
+const HELP: &str = "\
+Usage: TinyWiiBackupManagerInstaller.exe [OPTIONS]
+
+Without options, the graphical installer is shown.
+
+Options:
+  --silent              Install the latest version without showing the GUI
+  --uninstall           Uninstall without showing the GUI
+  --check-update        Only report whether an update is available on --channel
+  --repair              Reinstall the installed version without showing the GUI, keeping settings
+  --keep-user-data      With --uninstall, keep settings and any files added to the install dir
+  --install-dir <PATH>  Install into <PATH>\\TinyWiiBackupManager
+  --all-users           Install for everyone on this PC, requires administrator rights
+  --channel <CHANNEL>   Release channel: stable (default) or prerelease
+  --version <VERSION>   With --silent, install VERSION (e.g. 1.2.3) instead of the latest
+  --artifact-url <URL>  With --silent, install a CI build from its artifact URL instead of a release
+                        (authenticated with TWBM_ARTIFACT_TOKEN or GITHUB_TOKEN)
+  --dry-run             Download but only report what the install would change
+  --base-url <URL>      Download releases from a mirror of github.com/mq1/TinyWiiBackupManager
+                        (also TWBM_BASE_URL)
+  --mirror <URL>        Also try this mirror, downloading from whichever answers first.
+                        Can be given more than once (also TWBM_MIRRORS, comma separated)
+  --max-rate <KB/s>     Limit the download speed, in kilobytes per second
+  --json                Print one JSON object per line instead of text, for deployment tooling
+  --help                Print this help
+
+Exit codes:
+  0   Success (for --check-update: up to date)
+  1   The install or uninstall failed
+  2   Invalid arguments
+  10  With --check-update, an update is available";
+
+/// What --check-update exits with when there's a newer version
+const UPDATE_AVAILABLE: u8 = 10;
+
+#[derive(Default)]
+pub struct Args {
+    pub silent: bool,
+    pub uninstall: bool,
+    pub repair: bool,
+    pub check_update: bool,
+    pub keep_user_data: bool,
+    pub help: bool,
+    pub install_dir: Option<PathBuf>,
+    pub channel: Channel,
+    pub version: Option<String>,
+    pub dry_run: bool,
+    pub all_users: bool,
+    pub base_url: Option<String>,
+    pub mirrors: Vec<String>,
+    pub max_rate: Option<u64>,
+    pub artifact_url: Option<String>,
+    pub json: bool,
+}
+
+impl Args {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut parsed = Args::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--silent" => parsed.silent = true,
+                "--uninstall" => parsed.uninstall = true,
+                "--repair" => parsed.repair = true,
+                "--check-update" => parsed.check_update = true,
+                "--keep-user-data" => parsed.keep_user_data = true,
+                "--dry-run" => parsed.dry_run = true,
+                "--all-users" => parsed.all_users = true,
+                "--json" => parsed.json = true,
+                "--help" | "-h" | "/?" => parsed.help = true,
+                "--install-dir" => {
+                    let dir = args.next().ok_or(anyhow!("--install-dir needs a path"))?;
+                    parsed.install_dir = Some(PathBuf::from(dir));
+                }
+                "--version" => {
+                    let version = args.next().ok_or(anyhow!("--version needs a version"))?;
+                    parsed.version = Some(util::parse_version_input(&version)?);
+                }
+                "--base-url" => {
+                    let url = args.next().ok_or(anyhow!("--base-url needs a URL"))?;
+                    parsed.base_url = Some(util::parse_base_url(&url)?);
+                }
+                "--mirror" => {
+                    let url = args.next().ok_or(anyhow!("--mirror needs a URL"))?;
+                    parsed.mirrors.push(util::parse_base_url(&url)?);
+                }
+                "--artifact-url" => {
+                    let url = args.next().ok_or(anyhow!("--artifact-url needs a URL"))?;
+                    if !url.starts_with("https://") {
+                        bail!("--artifact-url must be an https URL");
+                    }
+                    parsed.artifact_url = Some(url);
+                }
+                "--max-rate" => {
+                    let rate = args
+                        .next()
+                        .ok_or(anyhow!("--max-rate needs a rate in KB/s"))?;
+                    parsed.max_rate = Some(util::parse_max_rate(&rate)?);
+                }
+                "--channel" => {
+                    parsed.channel = match args.next().as_deref() {
+                        Some("stable") => Channel::Stable,
+                        Some("prerelease") => Channel::Prerelease,
+                        _ => bail!("--channel must be either stable or prerelease"),
+                    };
+                }
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+
+        if parsed.version.is_some() && parsed.artifact_url.is_some() {
+            bail!("--version and --artifact-url can't be used together");
+        }
+
+        Ok(parsed)
+    }
+
+    /// Whether to skip the GUI entirely
+    pub fn is_headless(&self) -> bool {
+        self.silent || self.uninstall || self.repair || self.check_update || self.help
+    }
+}
+
+/// Makes println! reach the terminal we were started from, as release builds have no console
+pub fn attach_console() {
+    unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+/// What the command line reports. With --json each one is printed as an object on its
+/// own line, tagged by "event" with the fields alongside, e.g.
+///
+/// {"event":"downloading","version":"1.2.3","os":"windows","arch":"x86_64"}
+/// {"event":"download_progress","downloaded":1048576,"total":8388608}
+/// {"event":"installed","version":"1.2.3"}
+///
+/// A run ends with exactly one of installed, dry_run, uninstalled, repaired,
+/// update_available, up_to_date or error.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum CliEvent {
+    FetchingLatest {
+        channel: &'static str,
+    },
+    CheckingVersion {
+        version: String,
+    },
+    Downloading {
+        version: String,
+        #[serde(serialize_with = "os_id")]
+        os: Os,
+        #[serde(serialize_with = "arch_id")]
+        arch: Arch,
+    },
+    DownloadingArtifact {
+        url: String,
+    },
+    /// `total` is null when the server didn't say
+    DownloadProgress {
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    Verifying,
+    /// False for a CI build, which has no published checksum
+    Downloaded {
+        version: String,
+        checksum_verified: bool,
+    },
+    Installing {
+        install_dir: String,
+    },
+    Extracting,
+    ExtractedFile {
+        done: usize,
+        total: usize,
+    },
+    CreatingShortcuts,
+    WritingRegistry,
+    /// What the install would do, one line per step
+    DryRun {
+        install_dir: String,
+        actions: Vec<String>,
+    },
+    /// `actions` are the steps that ran, as in dry_run
+    Installed {
+        version: String,
+        install_dir: String,
+        elapsed_secs: f32,
+        actions: Vec<String>,
+    },
+    Uninstalling,
+    Uninstalled,
+    Repairing {
+        version: String,
+    },
+    Repaired {
+        version: String,
+    },
+    UpdateAvailable {
+        installed: String,
+        latest: String,
+    },
+    UpToDate {
+        installed: String,
+    },
+    /// Something that didn't stop the run
+    Warning {
+        message: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl CliEvent {
+    /// The text form, progress indented under the line it belongs to
+    fn print(&self) {
+        match self {
+            CliEvent::FetchingLatest { channel } => {
+                println!("Fetching latest {} version...", channel)
+            }
+            CliEvent::CheckingVersion { version } => println!("Checking v{}...", version),
+            CliEvent::Downloading { version, os, arch } => {
+                println!("Downloading v{} for {} {}...", version, os, arch)
+            }
+            CliEvent::DownloadingArtifact { url } => {
+                println!("Downloading the CI build from {}...", url)
+            }
+            CliEvent::DownloadProgress { downloaded, total } => {
+                if let Some(total) = total.filter(|total| *total > 0) {
+                    println!("  {}%", downloaded * 100 / total);
+                }
+            }
+            CliEvent::Verifying => println!("Verifying the download..."),
+            CliEvent::Downloaded {
+                checksum_verified: true,
+                ..
+            } => println!("Checksum verified"),
+            CliEvent::Downloaded { version, .. } => {
+                println!("Downloaded {}, it has no published checksum", version)
+            }
+            CliEvent::Installing { install_dir } => println!("Installing to {}...", install_dir),
+            CliEvent::Extracting => println!("  Extracting files..."),
+            // Only worth a line when the release bundles more than the exe
+            CliEvent::ExtractedFile { done, total } => {
+                if *total > 1 {
+                    println!("    {}/{}", done, total);
+                }
+            }
+            CliEvent::CreatingShortcuts => println!("  Creating shortcuts..."),
+            CliEvent::WritingRegistry => println!("  Updating the registry..."),
+            CliEvent::DryRun {
+                install_dir,
+                actions,
+            } => {
+                println!("Dry run, installing to {} would:", install_dir);
+                for action in actions {
+                    println!("  {}", action);
+                }
+            }
+            CliEvent::Installed {
+                version,
+                install_dir,
+                elapsed_secs,
+                ..
+            } => println!(
+                "TinyWiiBackupManager {} installed to {} in {:.1}s",
+                util::display_version(version),
+                install_dir,
+                elapsed_secs
+            ),
+            CliEvent::Uninstalling => println!("Uninstalling TinyWiiBackupManager..."),
+            CliEvent::Uninstalled => println!("TinyWiiBackupManager uninstalled"),
+            CliEvent::Repairing { version } => {
+                println!("Repairing TinyWiiBackupManager v{}...", version)
+            }
+            CliEvent::Repaired { version } => {
+                println!("TinyWiiBackupManager v{} repaired", version)
+            }
+            CliEvent::UpdateAvailable { installed, latest } => {
+                println!("Update available: v{} -> v{}", installed, latest)
+            }
+            CliEvent::UpToDate { installed } => println!("Up to date (v{})", installed),
+            CliEvent::Warning { message } => eprintln!("Warning: {}", message),
+            CliEvent::Error { message } => eprintln!("Error: {}", message),
+        }
+    }
+}
+
+/// Os and Arch go in the JSON by the ids used in the asset names, not their display names
+fn os_id<S: Serializer>(os: &Os, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(os.as_str())
+}
+
+fn arch_id<S: Serializer>(arch: &Arch, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(arch.as_str())
+}
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+fn emit(event: CliEvent) {
+    if !JSON.load(Ordering::Relaxed) {
+        event.print();
+        return;
+    }
+
+    // Can't fail, CliEvent has no maps and no non-string keys
+    if let Ok(line) = serde_json::to_string(&event) {
+        println!("{}", line);
+    }
+}
+
+/// Reports a run that failed, and how it exits
+fn failed(e: anyhow::Error) -> ExitCode {
+    emit(CliEvent::Error {
+        message: e.to_string(),
+    });
+    ExitCode::FAILURE
+}
+
+pub fn run(args: Args) -> ExitCode {
+    if args.help {
+        println!("{}", HELP);
+        return ExitCode::SUCCESS;
+    }
+
+    JSON.store(args.json, Ordering::Relaxed);
+
+    for url in args
+        .base_url
+        .iter()
+        .chain(&args.mirrors)
+        .filter(|url| url.starts_with("http://"))
+    {
+        emit(CliEvent::Warning {
+            message: format!(
+                "{} is not https, only the checksum protects the download",
+                url
+            ),
+        });
+    }
+
+    if args.check_update {
+        return match check_update(args.channel) {
+            Ok(true) => ExitCode::from(UPDATE_AVAILABLE),
+            Ok(false) => ExitCode::SUCCESS,
+            Err(e) => failed(e),
+        };
+    }
+
+    let res = if args.uninstall {
+        uninstall(args.keep_user_data)
+    } else if args.repair {
+        repair()
+    } else {
+        install(args)
+    };
+
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => failed(e),
+    }
+}
+
+pub fn print_usage_error(e: &anyhow::Error) -> ExitCode {
+    eprintln!("Error: {}\n\n{}", e, HELP);
+    ExitCode::from(2)
+}
+
+fn install(args: Args) -> Result<()> {
+    let os = util::get_os()?;
+    let arch = util::get_arch();
+    let scope = if args.all_users {
+        Scope::AllUsers
+    } else {
+        Scope::CurrentUser
+    };
+    let install_dir = match args.install_dir {
+        Some(dir) => util::install_dir_in(dir)?,
+        None if util::installed_scope() == Some(scope) => util::install_dir()?,
+        None => util::default_install_dir(scope)?,
+    };
+    let options = InstallOptions {
+        scope,
+        ..Default::default()
+    };
+
+    if util::is_running() {
+        bail!("TinyWiiBackupManager is running, please close it first");
+    }
+
+    if !util::can_write_to(&install_dir) || (scope == Scope::AllUsers && !util::is_elevated()) {
+        bail!(
+            "Installing to {} requires administrator rights, please run from an elevated prompt",
+            install_dir.display()
+        );
+    }
+
+    // A CI build skips version resolution, and has no checksum to verify
+    let (version, bytes) = match &args.artifact_url {
+        Some(url) => {
+            emit(CliEvent::DownloadingArtifact { url: url.clone() });
+            let mut on_progress = print_progress();
+            let (version, bytes) = util::download_artifact(url, |progress| {
+                on_progress(ProgressEvent::BytesDownloaded(progress))
+            })?;
+            emit(CliEvent::Downloaded {
+                version: version.clone(),
+                checksum_verified: false,
+            });
+            (version, bytes)
+        }
+        None => {
+            let version = match args.version {
+                Some(version) => {
+                    emit(CliEvent::CheckingVersion {
+                        version: version.clone(),
+                    });
+                    block_on(util::check_asset_exists(&version, os, arch))?;
+                    version
+                }
+                None => {
+                    emit(CliEvent::FetchingLatest {
+                        channel: args.channel.as_str(),
+                    });
+                    block_on(installer::fetch_latest(args.channel))?
+                }
+            };
+
+            emit(CliEvent::Downloading {
+                version: version.clone(),
+                os,
+                arch,
+            });
+            let download = block_on(installer::download(version, os, arch, print_progress()))?;
+            emit(CliEvent::Downloaded {
+                version: download.0.clone(),
+                checksum_verified: true,
+            });
+            if !installer::checks_signature() {
+                emit(CliEvent::Warning {
+                    message: "this installer has no release signing key, so the download's \
+                              signature wasn't checked"
+                        .to_string(),
+                });
+            }
+            download
+        }
+    };
+
+    if util::is_dry_run() {
+        let actions = util::plan_install(&install_dir, options)?;
+        emit(CliEvent::DryRun {
+            install_dir: install_dir.display().to_string(),
+            actions: actions.iter().map(|action| action.to_string()).collect(),
+        });
+
+        return Ok(());
+    }
+
+    emit(CliEvent::Installing {
+        install_dir: install_dir.display().to_string(),
+    });
+    let report = block_on(installer::install(
+        version,
+        bytes,
+        install_dir,
+        options,
+        print_progress(),
+    ))?;
+    for message in report.warnings {
+        emit(CliEvent::Warning { message });
+    }
+
+    emit(CliEvent::Installed {
+        version: report.version,
+        install_dir: report.install_dir.display().to_string(),
+        elapsed_secs: report.elapsed.as_secs_f32(),
+        actions: report
+            .actions
+            .iter()
+            .map(|action| action.to_string())
+            .collect(),
+    });
+    Ok(())
+}
+
+/// Reports the download every 10% (every 1% with --json), and each kind of install step as it starts
+fn print_progress() -> impl FnMut(ProgressEvent) -> ControlFlow<()> {
+    let step = if JSON.load(Ordering::Relaxed) { 1 } else { 10 };
+    let mut last_percent = 0;
+
+    move |event| {
+        let event = match event {
+            ProgressEvent::BytesDownloaded(progress) => {
+                let Some(total) = progress.total.filter(|total| *total > 0) else {
+                    return ControlFlow::Continue(());
+                };
+                let percent = progress.downloaded * 100 / total;
+                if percent / step <= last_percent / step {
+                    return ControlFlow::Continue(());
+                }
+                last_percent = percent;
+
+                CliEvent::DownloadProgress {
+                    downloaded: progress.downloaded,
+                    total: progress.total,
+                }
+            }
+            ProgressEvent::Verifying => CliEvent::Verifying,
+            ProgressEvent::Extracting => CliEvent::Extracting,
+            ProgressEvent::ExtractedFile { done, total } => CliEvent::ExtractedFile { done, total },
+            ProgressEvent::CreatingShortcuts => CliEvent::CreatingShortcuts,
+            ProgressEvent::WritingRegistry => CliEvent::WritingRegistry,
+        };
+        emit(event);
+
+        ControlFlow::Continue(())
+    }
+}
+
+fn uninstall(keep_user_data: bool) -> Result<()> {
+    emit(CliEvent::Uninstalling);
+    installer::uninstall(keep_user_data)?;
+
+    emit(CliEvent::Uninstalled);
+    Ok(())
+}
+
+fn repair() -> Result<()> {
+    let version =
+        util::installed_version()?.ok_or(anyhow!("TinyWiiBackupManager is not installed"))?;
+    let os = util::get_os()?;
+    let arch = util::get_arch();
+
+    emit(CliEvent::Repairing {
+        version: version.clone(),
+    });
+    let version = block_on(installer::repair(version, os, arch, print_progress()))?;
+
+    emit(CliEvent::Repaired { version });
+    Ok(())
+}
+
+/// Whether a newer version than the installed one is out, without changing anything
+fn check_update(channel: Channel) -> Result<bool> {
+    let installed =
+        util::installed_version()?.ok_or(anyhow!("TinyWiiBackupManager is not installed"))?;
+    let latest = block_on(installer::fetch_latest(channel))?;
+
+    if util::is_newer(&latest, &installed) {
+        emit(CliEvent::UpdateAvailable { installed, latest });
+        Ok(true)
+    } else {
+        emit(CliEvent::UpToDate { installed });
+        Ok(false)
+    }
+}
@@ -0,0 +1,1059 @@
+// SPDX-FileCopyrightText: 2026 Manuel Quarneti <mq1@ik.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! UI strings by language. Adding a language means adding a `Lang` variant and its table;
+//! anything a table is missing falls back to English.
+
+use std::fmt;
+use windows_sys::Win32::Globalization::GetUserDefaultUILanguage;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    English,
+    Italian,
+    Spanish,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 3] = [Lang::English, Lang::Italian, Lang::Spanish];
+
+    /// The code used to save the preference
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Lang::English => "en",
+            Lang::Italian => "it",
+            Lang::Spanish => "es",
+        }
+    }
+
+    /// The language's own name for itself
+    pub fn as_display_str(&self) -> &'static str {
+        match self {
+            Lang::English => "English",
+            Lang::Italian => "Italiano",
+            Lang::Spanish => "Español",
+        }
+    }
+
+    /// The Windows display language, or English if we don't have it
+    pub fn detect() -> Lang {
+        let lang_id = unsafe { GetUserDefaultUILanguage() };
+
+        // The primary language is in the low 10 bits
+        match lang_id & 0x3ff {
+            0x10 => Lang::Italian,
+            0x0a => Lang::Spanish,
+            _ => Lang::English,
+        }
+    }
+
+    fn table(&self) -> &'static [(Key, &'static str)] {
+        match self {
+            Lang::English => EN,
+            Lang::Italian => IT,
+            Lang::Spanish => ES,
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_display_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    StageFetch,
+    StageDownload,
+    StageInstall,
+    StageDone,
+    FetchingLatest,
+    FetchingLatestPrerelease,
+    CouldNotFetch,
+    Offline,
+    OfflineHint,
+    Retry,
+    InstallFromFile,
+    Detected,
+    DetectedUnknown,
+    Reinstall,
+    Downgrade,
+    Update,
+    DownloadAndInstallVersion,
+    DownloadAndInstall,
+    LatestVersion,
+    InstalledVersion,
+    InstalledNightly,
+    Uninstall,
+    RepairInstallation,
+    ShortcutsBroken,
+    FixShortcuts,
+    AlreadyInstalled,
+    Location,
+    InstalledOn,
+    ShortcutsOk,
+    ShortcutsMissing,
+    CheckingForUpdates,
+    CouldNotCheckForUpdates,
+    UpdateAvailable,
+    UpToDate,
+    SkipVersion,
+    VerifyInstallation,
+    Verifying,
+    VerifyIntact,
+    VerifyModified,
+    VerifyUnavailable,
+    VerifyFailed,
+    VersionSkipped,
+    Welcome,
+    WelcomeSummary,
+    DontShowAgain,
+    MoreOptions,
+    FilesModified,
+    Os,
+    Arch,
+    NoV3Build,
+    ArchIncompatible,
+    ArchIncompatibleReview,
+    InstallTo,
+    FreeSpace,
+    Change,
+    NotEnoughSpace,
+    NetworkSettings,
+    Proxy,
+    ProxyPlaceholder,
+    MaxRate,
+    MaxRatePlaceholder,
+    InstallDifferentVersion,
+    Install,
+    DesktopShortcut,
+    StartMenuShortcut,
+    PinToTaskbar,
+    AddToPath,
+    OpenWbfs,
+    IsoToo,
+    CreateRestorePoint,
+    InstallPortable,
+    StartWithWindows,
+    DownloadPortable,
+    V3Fallback,
+    SignatureNotChecked,
+    Downloading,
+    Cancel,
+    CancellingInstall,
+    Installing,
+    Extracting,
+    ExtractedFiles,
+    CreatingShortcuts,
+    WritingRegistry,
+    DryRunDone,
+    DryRunSeeLog,
+    Close,
+    TryAgain,
+    InstalledSummary,
+    SummaryRestorePoint,
+    SummaryDesktopShortcut,
+    SummaryStartMenuShortcut,
+    SummaryPinToTaskbar,
+    SummaryAddToPath,
+    SummaryFileAssociations,
+    SummaryFileAssociationsIso,
+    SummaryRunOnStartup,
+    ConfirmExitTitle,
+    ConfirmExit,
+    Installed,
+    Launch,
+    LaunchAndClose,
+    OpenInstallFolder,
+    PortableSaved,
+    ShowInFolder,
+    RunPortable,
+    AskUninstall,
+    UninstallKeepData,
+    UninstallEverything,
+    Uninstalling,
+    Uninstalled,
+    NeedsAdmin,
+    Back,
+    RelaunchAsAdmin,
+    ChangesWouldBeMade,
+    ChangesWillBeMade,
+    AppRunning,
+    CloseItForMe,
+    MeteredConnection,
+    Continue,
+    Advanced,
+    DownloadUrl,
+    Copy,
+    ClearCache,
+    Remaining,
+    HintPermission,
+    HintChecksum,
+    HintInUse,
+    HintBadDownload,
+    HintSignature,
+    UnsupportedOs,
+    Error,
+    CopyErrorDetails,
+    ReportProblem,
+    WindowTitle,
+    DialogTitle,
+    SelectInstallDir,
+    SelectDestinationDir,
+    SelectZip,
+    ZipArchive,
+    Crashed,
+    CrashedReportSaved,
+    Details,
+}
+
+/// The string for `key`, with `{0}`, `{1}`... left in for `trf` to fill
+pub fn tr(key: Key, lang: Lang) -> &'static str {
+    let find = |table: &'static [(Key, &'static str)]| {
+        table
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, string)| *string)
+    };
+
+    find(lang.table()).or_else(|| find(EN)).unwrap_or_default()
+}
+
+/// `tr` with the placeholders filled in, so each language can put them where it needs to
+pub fn trf(key: Key, lang: Lang, args: &[&dyn fmt::Display]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(tr(key, lang).to_string(), |string, (i, arg)| {
+            string.replace(&format!("{{{}}}", i), &arg.to_string())
+        })
+}
+
+const EN: &[(Key, &str)] = &[
+    (Key::StageFetch, "Fetch"),
+    (Key::StageDownload, "Download"),
+    (Key::StageInstall, "Install"),
+    (Key::StageDone, "Done"),
+    (Key::FetchingLatest, "{0} Fetching latest version..."),
+    (
+        Key::FetchingLatestPrerelease,
+        "{0} Fetching latest pre-release...",
+    ),
+    (
+        Key::CouldNotFetch,
+        "Could not fetch the latest version: {0}",
+    ),
+    (Key::Offline, "You appear to be offline"),
+    (
+        Key::OfflineHint,
+        "Check your connection and try again, or install from a downloaded file",
+    ),
+    (Key::Retry, "Retry"),
+    (Key::InstallFromFile, "Install from file…"),
+    (Key::Detected, "Detected: {0} (build {1})"),
+    (Key::DetectedUnknown, "Detected: unknown build"),
+    (Key::Reinstall, "Reinstall v{0} (up to date)"),
+    (Key::Downgrade, "Downgrade to v{0}"),
+    (Key::Update, "Update to v{0}"),
+    (Key::DownloadAndInstallVersion, "Download and Install v{0}"),
+    (Key::DownloadAndInstall, "Download and Install"),
+    (Key::LatestVersion, "Latest version: v{0}"),
+    (Key::InstalledVersion, "Installed version: v{0}"),
+    (
+        Key::InstalledNightly,
+        "Installed version: {0} (nightly build)",
+    ),
+    (Key::Uninstall, "Uninstall"),
+    (Key::RepairInstallation, "Repair installation"),
+    (
+        Key::ShortcutsBroken,
+        "The shortcuts don't open the installed app anymore",
+    ),
+    (Key::FixShortcuts, "Fix shortcuts"),
+    (Key::AlreadyInstalled, "TinyWiiBackupManager is installed"),
+    (Key::Location, "Location: {0}"),
+    (Key::InstalledOn, "Installed on {0}"),
+    (Key::ShortcutsOk, "The shortcuts are in place"),
+    (Key::ShortcutsMissing, "Some shortcuts are missing"),
+    (Key::CheckingForUpdates, "{0} Checking for updates..."),
+    (Key::CouldNotCheckForUpdates, "Could not check for updates"),
+    (Key::UpdateAvailable, "v{0} is available"),
+    (Key::UpToDate, "Up to date"),
+    (Key::SkipVersion, "Skip this version"),
+    (Key::VerifyInstallation, "Verify installation"),
+    (Key::Verifying, "Verifying..."),
+    (
+        Key::VerifyIntact,
+        "The installed files match the v{0} release",
+    ),
+    (
+        Key::VerifyModified,
+        "The installed files differ from the v{0} release, a repair will fix them",
+    ),
+    (
+        Key::VerifyUnavailable,
+        "v{0} is no longer on the server, so it can't be verified",
+    ),
+    (Key::VerifyFailed, "Could not verify the installation: {0}"),
+    (Key::VersionSkipped, "v{0} is available (skipped)"),
+    (Key::Welcome, "Welcome!"),
+    (
+        Key::WelcomeSummary,
+        "We'll install TinyWiiBackupManager v{0} to {1}",
+    ),
+    (Key::DontShowAgain, "Don't show this again"),
+    (Key::MoreOptions, "Other versions and options…"),
+    (
+        Key::FilesModified,
+        "The installed files don't match v{0}, try repairing",
+    ),
+    (Key::Os, "OS:"),
+    (Key::Arch, "Arch:"),
+    (Key::NoV3Build, "No x86_64-v3 build available"),
+    (
+        Key::ArchIncompatible,
+        "This build may not run on your processor",
+    ),
+    (
+        Key::ArchIncompatibleReview,
+        "The {0} build may not run on your processor, only continue if you're sure it will",
+    ),
+    (Key::InstallTo, "Install to: {0}"),
+    (Key::FreeSpace, "({0} free)"),
+    (Key::Change, "Change…"),
+    (
+        Key::NotEnoughSpace,
+        "Not enough free space, at least {0} is needed",
+    ),
+    (Key::NetworkSettings, "Network settings"),
+    (Key::Proxy, "Proxy:"),
+    (
+        Key::ProxyPlaceholder,
+        "http://host:port (defaults to HTTPS_PROXY)",
+    ),
+    (Key::MaxRate, "Max download speed (KB/s):"),
+    (Key::MaxRatePlaceholder, "Unlimited"),
+    (Key::InstallDifferentVersion, "Install a different version"),
+    (Key::Install, "Install"),
+    (Key::DesktopShortcut, "Desktop shortcut"),
+    (Key::StartMenuShortcut, "Start menu shortcut"),
+    (Key::PinToTaskbar, "Pin to taskbar"),
+    (Key::AddToPath, "Add to PATH"),
+    (Key::OpenWbfs, "Open .wbfs files"),
+    (Key::IsoToo, ".iso too"),
+    (Key::CreateRestorePoint, "Create a system restore point"),
+    (
+        Key::InstallPortable,
+        "Install portable (no registry/shortcuts)",
+    ),
+    (Key::StartWithWindows, "Start with Windows"),
+    (Key::DownloadPortable, "Download Portable"),
+    (
+        Key::V3Fallback,
+        "There's no x86_64-v3 build of this version, using the x86_64 one",
+    ),
+    (
+        Key::SignatureNotChecked,
+        "This installer has no release signing key, so the download's signature won't be checked",
+    ),
+    (Key::Downloading, "Downloading v{0}"),
+    (Key::Cancel, "Cancel"),
+    (
+        Key::CancellingInstall,
+        "{0} Cancelling, undoing what was installed...",
+    ),
+    (Key::Installing, "Installing v{0}"),
+    (Key::Extracting, "Extracting files..."),
+    (Key::ExtractedFiles, "Extracting files... ({0}/{1})"),
+    (Key::CreatingShortcuts, "Creating shortcuts..."),
+    (Key::WritingRegistry, "Updating the registry..."),
+    (
+        Key::DryRunDone,
+        "Dry run of v{0} finished, nothing was changed",
+    ),
+    (
+        Key::DryRunSeeLog,
+        "See install.log for what would have been done",
+    ),
+    (Key::Close, "Close"),
+    (Key::TryAgain, "Try again"),
+    (Key::ConfirmExitTitle, "Close the installer?"),
+    (
+        Key::ConfirmExit,
+        "Closing now could leave TinyWiiBackupManager partly installed or uninstalled. Close anyway?",
+    ),
+    (Key::Installed, "TinyWiiBackupManager v{0} installed"),
+    (Key::InstalledSummary, "In {0}, took {1}"),
+    (Key::SummaryRestorePoint, "Created a system restore point"),
+    (Key::SummaryDesktopShortcut, "Added a desktop shortcut"),
+    (Key::SummaryStartMenuShortcut, "Added a Start menu shortcut"),
+    (Key::SummaryPinToTaskbar, "Tried to pin it to the taskbar"),
+    (Key::SummaryAddToPath, "Added it to PATH"),
+    (Key::SummaryFileAssociations, "Set it to open .wbfs files"),
+    (
+        Key::SummaryFileAssociationsIso,
+        "Set it to open .wbfs and .iso files",
+    ),
+    (Key::SummaryRunOnStartup, "Set it to start with Windows"),
+    (Key::Launch, "Launch"),
+    (Key::LaunchAndClose, "→ Launch and close installer"),
+    (Key::OpenInstallFolder, "Open install folder"),
+    (
+        Key::PortableSaved,
+        "TinyWiiBackupManager v{0} (portable) saved to:",
+    ),
+    (Key::ShowInFolder, "Show in folder"),
+    (Key::RunPortable, "→ Run portable"),
+    (
+        Key::AskUninstall,
+        "Do you want to uninstall TinyWiiBackupManager?",
+    ),
+    (Key::UninstallKeepData, "Uninstall, keep my data"),
+    (Key::UninstallEverything, "Uninstall everything"),
+    (Key::Uninstalling, "Uninstalling..."),
+    (
+        Key::Uninstalled,
+        "TinyWiiBackupManager has been successfully uninstalled",
+    ),
+    (
+        Key::NeedsAdmin,
+        "Installing to {0} requires administrator rights",
+    ),
+    (Key::Back, "Back"),
+    (Key::RelaunchAsAdmin, "Relaunch as administrator"),
+    (
+        Key::ChangesWouldBeMade,
+        "Dry run, the following changes would be made:",
+    ),
+    (
+        Key::ChangesWillBeMade,
+        "The following changes will be made:",
+    ),
+    (
+        Key::AppRunning,
+        "TinyWiiBackupManager is running, please close it to continue",
+    ),
+    (Key::CloseItForMe, "Close it for me"),
+    (
+        Key::UnsupportedOs,
+        "This installer requires Windows 7 or later",
+    ),
+    (
+        Key::HintSignature,
+        "Do not install this download, it is not the official release",
+    ),
+    (
+        Key::MeteredConnection,
+        "You appear to be on a metered connection. Continue?",
+    ),
+    (Key::Continue, "Continue"),
+    (Key::Advanced, "Advanced"),
+    (Key::DownloadUrl, "Download URL:"),
+    (Key::Copy, "Copy"),
+    (Key::Remaining, "~{0} remaining"),
+    (Key::ClearCache, "Clear download cache"),
+    (Key::HintPermission, "Try installing to a folder you own"),
+    (
+        Key::HintChecksum,
+        "The download may be corrupted, please try again",
+    ),
+    (
+        Key::HintInUse,
+        "Make sure TinyWiiBackupManager isn't running",
+    ),
+    (
+        Key::HintBadDownload,
+        "The server sent something else, you may need to sign in to your network first",
+    ),
+    (Key::Error, "Error: {0}"),
+    (Key::CopyErrorDetails, "Copy error details"),
+    (Key::ReportProblem, "Report a problem"),
+    (Key::WindowTitle, "Install TinyWiiBackupManager"),
+    (Key::DialogTitle, "TinyWiiBackupManager Installer"),
+    (Key::SelectInstallDir, "Select installation directory"),
+    (Key::SelectDestinationDir, "Select destination directory"),
+    (Key::SelectZip, "Select a TinyWiiBackupManager .zip"),
+    (Key::ZipArchive, "Zip archive"),
+    (
+        Key::Crashed,
+        "The installer ran into a bug and has to close.",
+    ),
+    (
+        Key::CrashedReportSaved,
+        "The installer ran into a bug and has to close.\n\nThe details were saved to {0}, please attach it when reporting the issue.",
+    ),
+    (Key::Details, "Details"),
+];
+
+const IT: &[(Key, &str)] = &[
+    (Key::StageFetch, "Verifica"),
+    (Key::StageDownload, "Download"),
+    (Key::StageInstall, "Installazione"),
+    (Key::StageDone, "Fine"),
+    (Key::FetchingLatest, "{0} Recupero dell'ultima versione..."),
+    (
+        Key::FetchingLatestPrerelease,
+        "{0} Recupero dell'ultima pre-release...",
+    ),
+    (
+        Key::CouldNotFetch,
+        "Impossibile recuperare l'ultima versione: {0}",
+    ),
+    (Key::Offline, "Sembra che tu sia offline"),
+    (
+        Key::OfflineHint,
+        "Controlla la connessione e riprova, oppure installa da un file scaricato",
+    ),
+    (Key::Retry, "Riprova"),
+    (Key::InstallFromFile, "Installa da file…"),
+    (Key::Detected, "Rilevato: {0} (build {1})"),
+    (Key::DetectedUnknown, "Rilevato: build sconosciuta"),
+    (Key::Reinstall, "Reinstalla v{0} (aggiornato)"),
+    (Key::Downgrade, "Torna alla v{0}"),
+    (Key::Update, "Aggiorna alla v{0}"),
+    (Key::DownloadAndInstallVersion, "Scarica e installa la v{0}"),
+    (Key::DownloadAndInstall, "Scarica e installa"),
+    (Key::LatestVersion, "Ultima versione: v{0}"),
+    (Key::InstalledVersion, "Versione installata: v{0}"),
+    (
+        Key::InstalledNightly,
+        "Versione installata: {0} (build nightly)",
+    ),
+    (Key::Uninstall, "Disinstalla"),
+    (Key::RepairInstallation, "Ripara installazione"),
+    (
+        Key::ShortcutsBroken,
+        "I collegamenti non aprono più l'app installata",
+    ),
+    (Key::FixShortcuts, "Ripara collegamenti"),
+    (Key::AlreadyInstalled, "TinyWiiBackupManager è installato"),
+    (Key::Location, "Percorso: {0}"),
+    (Key::InstalledOn, "Installato il {0}"),
+    (Key::ShortcutsOk, "I collegamenti sono a posto"),
+    (Key::ShortcutsMissing, "Mancano alcuni collegamenti"),
+    (Key::CheckingForUpdates, "{0} Ricerca aggiornamenti..."),
+    (
+        Key::CouldNotCheckForUpdates,
+        "Impossibile cercare aggiornamenti",
+    ),
+    (Key::UpdateAvailable, "È disponibile la v{0}"),
+    (Key::UpToDate, "Aggiornato"),
+    (Key::SkipVersion, "Salta questa versione"),
+    (Key::VerifyInstallation, "Verifica installazione"),
+    (Key::Verifying, "Verifica in corso..."),
+    (
+        Key::VerifyIntact,
+        "I file installati corrispondono alla release v{0}",
+    ),
+    (
+        Key::VerifyModified,
+        "I file installati sono diversi dalla release v{0}, una riparazione li sistemerà",
+    ),
+    (
+        Key::VerifyUnavailable,
+        "La v{0} non è più sul server, quindi non può essere verificata",
+    ),
+    (
+        Key::VerifyFailed,
+        "Impossibile verificare l'installazione: {0}",
+    ),
+    (Key::VersionSkipped, "È disponibile la v{0} (saltata)"),
+    (Key::Welcome, "Benvenuto!"),
+    (
+        Key::WelcomeSummary,
+        "Installeremo TinyWiiBackupManager v{0} in {1}",
+    ),
+    (Key::DontShowAgain, "Non mostrare più"),
+    (Key::MoreOptions, "Altre versioni e opzioni…"),
+    (
+        Key::FilesModified,
+        "I file installati non corrispondono alla v{0}, prova a ripararli",
+    ),
+    (Key::Os, "SO:"),
+    (Key::Arch, "Arch:"),
+    (Key::NoV3Build, "Nessuna build x86_64-v3 disponibile"),
+    (
+        Key::ArchIncompatible,
+        "Questa build potrebbe non funzionare sul tuo processore",
+    ),
+    (
+        Key::ArchIncompatibleReview,
+        "La build {0} potrebbe non funzionare sul tuo processore, continua solo se sei sicuro",
+    ),
+    (Key::InstallTo, "Installa in: {0}"),
+    (Key::FreeSpace, "({0} liberi)"),
+    (Key::Change, "Cambia…"),
+    (
+        Key::NotEnoughSpace,
+        "Spazio libero insufficiente, servono almeno {0}",
+    ),
+    (Key::NetworkSettings, "Impostazioni di rete"),
+    (Key::Proxy, "Proxy:"),
+    (
+        Key::ProxyPlaceholder,
+        "http://host:porta (predefinito HTTPS_PROXY)",
+    ),
+    (Key::MaxRate, "Velocità massima di download (KB/s):"),
+    (Key::MaxRatePlaceholder, "Illimitata"),
+    (Key::InstallDifferentVersion, "Installa un'altra versione"),
+    (Key::Install, "Installa"),
+    (Key::DesktopShortcut, "Collegamento sul desktop"),
+    (Key::StartMenuShortcut, "Collegamento nel menu Start"),
+    (Key::PinToTaskbar, "Aggiungi alla barra delle applicazioni"),
+    (Key::AddToPath, "Aggiungi al PATH"),
+    (Key::OpenWbfs, "Apri i file .wbfs"),
+    (Key::IsoToo, "anche .iso"),
+    (
+        Key::CreateRestorePoint,
+        "Crea un punto di ripristino del sistema",
+    ),
+    (
+        Key::InstallPortable,
+        "Installa portable (senza registro/collegamenti)",
+    ),
+    (Key::StartWithWindows, "Avvia con Windows"),
+    (Key::DownloadPortable, "Scarica portable"),
+    (
+        Key::V3Fallback,
+        "Non c'è una build x86_64-v3 di questa versione, uso quella x86_64",
+    ),
+    (
+        Key::SignatureNotChecked,
+        "Questo installer non ha la chiave di firma delle release, la firma del download non verrà verificata",
+    ),
+    (Key::Downloading, "Download della v{0}"),
+    (Key::Cancel, "Annulla"),
+    (
+        Key::CancellingInstall,
+        "{0} Annullamento, ripristino di quanto installato...",
+    ),
+    (Key::Installing, "Installazione della v{0}"),
+    (Key::Extracting, "Estrazione dei file..."),
+    (Key::ExtractedFiles, "Estrazione dei file... ({0}/{1})"),
+    (Key::CreatingShortcuts, "Creazione dei collegamenti..."),
+    (Key::WritingRegistry, "Aggiornamento del registro..."),
+    (
+        Key::DryRunDone,
+        "Prova della v{0} completata, non è stato modificato nulla",
+    ),
+    (
+        Key::DryRunSeeLog,
+        "Vedi install.log per cosa sarebbe stato fatto",
+    ),
+    (Key::Close, "Chiudi"),
+    (Key::TryAgain, "Riprova"),
+    (
+        Key::ConfirmExitTitle,
+        "Chiudere il programma di installazione?",
+    ),
+    (
+        Key::ConfirmExit,
+        "Chiudendo ora TinyWiiBackupManager potrebbe restare installato o disinstallato solo in parte. Chiudere comunque?",
+    ),
+    (Key::Installed, "TinyWiiBackupManager v{0} installato"),
+    (Key::InstalledSummary, "In {0}, ci sono voluti {1}"),
+    (Key::SummaryRestorePoint, "Creato un punto di ripristino"),
+    (
+        Key::SummaryDesktopShortcut,
+        "Aggiunto un collegamento sul desktop",
+    ),
+    (
+        Key::SummaryStartMenuShortcut,
+        "Aggiunto un collegamento nel menu Start",
+    ),
+    (
+        Key::SummaryPinToTaskbar,
+        "Tentato l'aggiunta alla barra delle applicazioni",
+    ),
+    (Key::SummaryAddToPath, "Aggiunto al PATH"),
+    (
+        Key::SummaryFileAssociations,
+        "Impostato per aprire i file .wbfs",
+    ),
+    (
+        Key::SummaryFileAssociationsIso,
+        "Impostato per aprire i file .wbfs e .iso",
+    ),
+    (
+        Key::SummaryRunOnStartup,
+        "Impostato per avviarsi con Windows",
+    ),
+    (Key::Launch, "Avvia"),
+    (Key::LaunchAndClose, "→ Avvia e chiudi l'installer"),
+    (Key::OpenInstallFolder, "Apri la cartella di installazione"),
+    (
+        Key::PortableSaved,
+        "TinyWiiBackupManager v{0} (portable) salvato in:",
+    ),
+    (Key::ShowInFolder, "Mostra nella cartella"),
+    (Key::RunPortable, "→ Avvia portable"),
+    (
+        Key::AskUninstall,
+        "Vuoi disinstallare TinyWiiBackupManager?",
+    ),
+    (Key::UninstallKeepData, "Disinstalla, mantieni i miei dati"),
+    (Key::UninstallEverything, "Disinstalla tutto"),
+    (Key::Uninstalling, "Disinstallazione..."),
+    (
+        Key::Uninstalled,
+        "TinyWiiBackupManager è stato disinstallato",
+    ),
+    (
+        Key::NeedsAdmin,
+        "Per installare in {0} servono i permessi di amministratore",
+    ),
+    (Key::Back, "Indietro"),
+    (Key::RelaunchAsAdmin, "Riavvia come amministratore"),
+    (
+        Key::ChangesWouldBeMade,
+        "Prova, verrebbero fatte queste modifiche:",
+    ),
+    (Key::ChangesWillBeMade, "Verranno fatte queste modifiche:"),
+    (
+        Key::AppRunning,
+        "TinyWiiBackupManager è in esecuzione, chiudilo per continuare",
+    ),
+    (Key::CloseItForMe, "Chiudilo tu"),
+    (
+        Key::UnsupportedOs,
+        "Questo installer richiede Windows 7 o successivo",
+    ),
+    (
+        Key::HintSignature,
+        "Non installare questo download, non è la release ufficiale",
+    ),
+    (
+        Key::MeteredConnection,
+        "Sembra che tu sia su una connessione a consumo. Continuare?",
+    ),
+    (Key::Continue, "Continua"),
+    (Key::Advanced, "Avanzate"),
+    (Key::DownloadUrl, "URL di download:"),
+    (Key::Copy, "Copia"),
+    (Key::Remaining, "~{0} rimanenti"),
+    (Key::ClearCache, "Svuota la cache dei download"),
+    (
+        Key::HintPermission,
+        "Prova a installare in una cartella di tua proprietà",
+    ),
+    (
+        Key::HintChecksum,
+        "Il download potrebbe essere danneggiato, riprova",
+    ),
+    (
+        Key::HintInUse,
+        "Assicurati che TinyWiiBackupManager non sia in esecuzione",
+    ),
+    (
+        Key::HintBadDownload,
+        "Il server ha inviato altro, potrebbe essere necessario accedere prima alla rete",
+    ),
+    (Key::Error, "Errore: {0}"),
+    (Key::CopyErrorDetails, "Copia i dettagli dell'errore"),
+    (Key::ReportProblem, "Segnala un problema"),
+    (Key::WindowTitle, "Installa TinyWiiBackupManager"),
+    (
+        Key::DialogTitle,
+        "Programma di installazione di TinyWiiBackupManager",
+    ),
+    (
+        Key::SelectInstallDir,
+        "Seleziona la cartella di installazione",
+    ),
+    (
+        Key::SelectDestinationDir,
+        "Seleziona la cartella di destinazione",
+    ),
+    (Key::SelectZip, "Seleziona un .zip di TinyWiiBackupManager"),
+    (Key::ZipArchive, "Archivio zip"),
+    (
+        Key::Crashed,
+        "Il programma di installazione ha incontrato un bug e deve chiudersi.",
+    ),
+    (
+        Key::CrashedReportSaved,
+        "Il programma di installazione ha incontrato un bug e deve chiudersi.\n\nI dettagli sono stati salvati in {0}, allegalo quando segnali il problema.",
+    ),
+    (Key::Details, "Dettagli"),
+];
+
+const ES: &[(Key, &str)] = &[
+    (Key::StageFetch, "Buscar"),
+    (Key::StageDownload, "Descargar"),
+    (Key::StageInstall, "Instalar"),
+    (Key::StageDone, "Listo"),
+    (Key::FetchingLatest, "{0} Buscando la última versión..."),
+    (
+        Key::FetchingLatestPrerelease,
+        "{0} Buscando la última pre-release...",
+    ),
+    (
+        Key::CouldNotFetch,
+        "No se pudo obtener la última versión: {0}",
+    ),
+    (Key::Offline, "Parece que no tienes conexión"),
+    (
+        Key::OfflineHint,
+        "Comprueba tu conexión y vuelve a intentarlo, o instala desde un archivo descargado",
+    ),
+    (Key::Retry, "Reintentar"),
+    (Key::InstallFromFile, "Instalar desde archivo…"),
+    (Key::Detected, "Detectado: {0} (build {1})"),
+    (Key::DetectedUnknown, "Detectado: build desconocida"),
+    (Key::Reinstall, "Reinstalar v{0} (actualizado)"),
+    (Key::Downgrade, "Volver a v{0}"),
+    (Key::Update, "Actualizar a v{0}"),
+    (Key::DownloadAndInstallVersion, "Descargar e instalar v{0}"),
+    (Key::DownloadAndInstall, "Descargar e instalar"),
+    (Key::LatestVersion, "Última versión: v{0}"),
+    (Key::InstalledVersion, "Versión instalada: v{0}"),
+    (
+        Key::InstalledNightly,
+        "Versión instalada: {0} (build nightly)",
+    ),
+    (Key::Uninstall, "Desinstalar"),
+    (Key::RepairInstallation, "Reparar instalación"),
+    (
+        Key::ShortcutsBroken,
+        "Los accesos directos ya no abren la aplicación instalada",
+    ),
+    (Key::FixShortcuts, "Reparar accesos directos"),
+    (Key::AlreadyInstalled, "TinyWiiBackupManager está instalado"),
+    (Key::Location, "Ubicación: {0}"),
+    (Key::InstalledOn, "Instalado el {0}"),
+    (Key::ShortcutsOk, "Los accesos directos están en su sitio"),
+    (Key::ShortcutsMissing, "Faltan algunos accesos directos"),
+    (Key::CheckingForUpdates, "{0} Buscando actualizaciones..."),
+    (
+        Key::CouldNotCheckForUpdates,
+        "No se pudieron buscar actualizaciones",
+    ),
+    (Key::UpdateAvailable, "La v{0} está disponible"),
+    (Key::UpToDate, "Actualizado"),
+    (Key::SkipVersion, "Omitir esta versión"),
+    (Key::VerifyInstallation, "Verificar instalación"),
+    (Key::Verifying, "Verificando..."),
+    (
+        Key::VerifyIntact,
+        "Los archivos instalados coinciden con la release v{0}",
+    ),
+    (
+        Key::VerifyModified,
+        "Los archivos instalados difieren de la release v{0}, una reparación los arreglará",
+    ),
+    (
+        Key::VerifyUnavailable,
+        "La v{0} ya no está en el servidor, así que no se puede verificar",
+    ),
+    (
+        Key::VerifyFailed,
+        "No se pudo verificar la instalación: {0}",
+    ),
+    (Key::VersionSkipped, "La v{0} está disponible (omitida)"),
+    (Key::Welcome, "¡Bienvenido!"),
+    (
+        Key::WelcomeSummary,
+        "Instalaremos TinyWiiBackupManager v{0} en {1}",
+    ),
+    (Key::DontShowAgain, "No volver a mostrar"),
+    (Key::MoreOptions, "Otras versiones y opciones…"),
+    (
+        Key::FilesModified,
+        "Los archivos instalados no coinciden con v{0}, prueba a repararlos",
+    ),
+    (Key::Os, "SO:"),
+    (Key::Arch, "Arq.:"),
+    (Key::NoV3Build, "No hay build x86_64-v3 disponible"),
+    (
+        Key::ArchIncompatible,
+        "Esta build podría no funcionar en tu procesador",
+    ),
+    (
+        Key::ArchIncompatibleReview,
+        "La build {0} podría no funcionar en tu procesador, continúa solo si estás seguro",
+    ),
+    (Key::InstallTo, "Instalar en: {0}"),
+    (Key::FreeSpace, "({0} libres)"),
+    (Key::Change, "Cambiar…"),
+    (
+        Key::NotEnoughSpace,
+        "No hay suficiente espacio libre, se necesitan al menos {0}",
+    ),
+    (Key::NetworkSettings, "Ajustes de red"),
+    (Key::Proxy, "Proxy:"),
+    (
+        Key::ProxyPlaceholder,
+        "http://host:puerto (por defecto HTTPS_PROXY)",
+    ),
+    (Key::MaxRate, "Velocidad máxima de descarga (KB/s):"),
+    (Key::MaxRatePlaceholder, "Ilimitada"),
+    (Key::InstallDifferentVersion, "Instalar otra versión"),
+    (Key::Install, "Instalar"),
+    (Key::DesktopShortcut, "Acceso directo en el escritorio"),
+    (Key::StartMenuShortcut, "Acceso directo en el menú Inicio"),
+    (Key::PinToTaskbar, "Anclar a la barra de tareas"),
+    (Key::AddToPath, "Añadir al PATH"),
+    (Key::OpenWbfs, "Abrir archivos .wbfs"),
+    (Key::IsoToo, "también .iso"),
+    (
+        Key::CreateRestorePoint,
+        "Crear un punto de restauración del sistema",
+    ),
+    (
+        Key::InstallPortable,
+        "Instalar portable (sin registro/accesos directos)",
+    ),
+    (Key::StartWithWindows, "Iniciar con Windows"),
+    (Key::DownloadPortable, "Descargar portable"),
+    (
+        Key::V3Fallback,
+        "No hay build x86_64-v3 de esta versión, se usa la x86_64",
+    ),
+    (
+        Key::SignatureNotChecked,
+        "Este instalador no tiene la clave de firma de las versiones, así que no se comprobará la firma de la descarga",
+    ),
+    (Key::Downloading, "Descargando v{0}"),
+    (Key::Cancel, "Cancelar"),
+    (
+        Key::CancellingInstall,
+        "{0} Cancelando, deshaciendo lo instalado...",
+    ),
+    (Key::Installing, "Instalando v{0}"),
+    (Key::Extracting, "Extrayendo archivos..."),
+    (Key::ExtractedFiles, "Extrayendo archivos... ({0}/{1})"),
+    (Key::CreatingShortcuts, "Creando accesos directos..."),
+    (Key::WritingRegistry, "Actualizando el registro..."),
+    (
+        Key::DryRunDone,
+        "Prueba de v{0} terminada, no se cambió nada",
+    ),
+    (
+        Key::DryRunSeeLog,
+        "Consulta install.log para ver qué se habría hecho",
+    ),
+    (Key::Close, "Cerrar"),
+    (Key::TryAgain, "Intentar de nuevo"),
+    (Key::ConfirmExitTitle, "¿Cerrar el instalador?"),
+    (
+        Key::ConfirmExit,
+        "Si cierra ahora, TinyWiiBackupManager podría quedar instalado o desinstalado a medias. ¿Cerrar de todos modos?",
+    ),
+    (Key::Installed, "TinyWiiBackupManager v{0} instalado"),
+    (Key::InstalledSummary, "En {0}, tardó {1}"),
+    (Key::SummaryRestorePoint, "Se creó un punto de restauración"),
+    (
+        Key::SummaryDesktopShortcut,
+        "Se añadió un acceso directo en el escritorio",
+    ),
+    (
+        Key::SummaryStartMenuShortcut,
+        "Se añadió un acceso directo en el menú Inicio",
+    ),
+    (
+        Key::SummaryPinToTaskbar,
+        "Se intentó anclarlo a la barra de tareas",
+    ),
+    (Key::SummaryAddToPath, "Se añadió al PATH"),
+    (
+        Key::SummaryFileAssociations,
+        "Se configuró para abrir archivos .wbfs",
+    ),
+    (
+        Key::SummaryFileAssociationsIso,
+        "Se configuró para abrir archivos .wbfs e .iso",
+    ),
+    (
+        Key::SummaryRunOnStartup,
+        "Se configuró para iniciarse con Windows",
+    ),
+    (Key::Launch, "Abrir"),
+    (Key::LaunchAndClose, "→ Abrir y cerrar el instalador"),
+    (Key::OpenInstallFolder, "Abrir la carpeta de instalación"),
+    (
+        Key::PortableSaved,
+        "TinyWiiBackupManager v{0} (portable) guardado en:",
+    ),
+    (Key::ShowInFolder, "Mostrar en la carpeta"),
+    (Key::RunPortable, "→ Abrir portable"),
+    (
+        Key::AskUninstall,
+        "¿Quieres desinstalar TinyWiiBackupManager?",
+    ),
+    (Key::UninstallKeepData, "Desinstalar, conservar mis datos"),
+    (Key::UninstallEverything, "Desinstalar todo"),
+    (Key::Uninstalling, "Desinstalando..."),
+    (
+        Key::Uninstalled,
+        "TinyWiiBackupManager se ha desinstalado correctamente",
+    ),
+    (
+        Key::NeedsAdmin,
+        "Instalar en {0} requiere permisos de administrador",
+    ),
+    (Key::Back, "Atrás"),
+    (Key::RelaunchAsAdmin, "Reiniciar como administrador"),
+    (Key::ChangesWouldBeMade, "Prueba, se harían estos cambios:"),
+    (Key::ChangesWillBeMade, "Se harán estos cambios:"),
+    (
+        Key::AppRunning,
+        "TinyWiiBackupManager se está ejecutando, ciérralo para continuar",
+    ),
+    (Key::CloseItForMe, "Ciérralo por mí"),
+    (
+        Key::UnsupportedOs,
+        "Este instalador requiere Windows 7 o posterior",
+    ),
+    (
+        Key::HintSignature,
+        "No instales esta descarga, no es la versión oficial",
+    ),
+    (
+        Key::MeteredConnection,
+        "Parece que estás en una conexión de uso medido. ¿Continuar?",
+    ),
+    (Key::Continue, "Continuar"),
+    (Key::Advanced, "Avanzado"),
+    (Key::DownloadUrl, "URL de descarga:"),
+    (Key::Copy, "Copiar"),
+    (Key::Remaining, "~{0} restantes"),
+    (Key::ClearCache, "Vaciar la caché de descargas"),
+    (Key::HintPermission, "Prueba a instalar en una carpeta tuya"),
+    (
+        Key::HintChecksum,
+        "La descarga puede estar dañada, inténtalo de nuevo",
+    ),
+    (
+        Key::HintInUse,
+        "Asegúrate de que TinyWiiBackupManager no se está ejecutando",
+    ),
+    (
+        Key::HintBadDownload,
+        "El servidor envió otra cosa, puede que primero tengas que iniciar sesión en tu red",
+    ),
+    (Key::Error, "Error: {0}"),
+    (Key::CopyErrorDetails, "Copiar detalles del error"),
+    (Key::ReportProblem, "Informar de un problema"),
+    (Key::WindowTitle, "Instalar TinyWiiBackupManager"),
+    (Key::DialogTitle, "Instalador de TinyWiiBackupManager"),
+    (
+        Key::SelectInstallDir,
+        "Selecciona la carpeta de instalación",
+    ),
+    (
+        Key::SelectDestinationDir,
+        "Selecciona la carpeta de destino",
+    ),
+    (Key::SelectZip, "Selecciona un .zip de TinyWiiBackupManager"),
+    (Key::ZipArchive, "Archivo zip"),
+    (
+        Key::Crashed,
+        "El instalador encontró un error y tiene que cerrarse.",
+    ),
+    (
+        Key::CrashedReportSaved,
+        "El instalador encontró un error y tiene que cerrarse.\n\nLos detalles se guardaron en {0}, adjúntalo al informar del problema.",
+    ),
+    (Key::Details, "Detalles"),
+];
@@ -0,0 +1,290 @@
+// SPDX-FileCopyrightText: 2026 Manuel Quarneti <mq1@ik.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The install flow without any UI, shared by the GUI and the command line.
+//! Nothing in here knows about iced.
+
+use crate::log;
+use crate::util::{
+    self, Arch, Channel, InstallError, InstallOptions, InstallReport, Os, Progress, ProgressEvent,
+};
+use anyhow::Result;
+use std::{
+    fs,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+/// What's installed on this machine right now
+pub struct Status {
+    pub installed_version: Option<String>,
+    /// The existing install's dir, or where a new one would go
+    pub install_dir: PathBuf,
+    pub is_installed: bool,
+    /// None if the install didn't record a hash to compare against
+    pub exe_matches: Option<bool>,
+    /// False if a desktop or Start menu shortcut points somewhere else
+    pub shortcuts_valid: bool,
+    /// False if the user deleted one of the shortcuts
+    pub shortcuts_exist: bool,
+    pub install_date: Option<String>,
+}
+
+pub fn status() -> Result<Status> {
+    Ok(Status {
+        installed_version: util::installed_version()?,
+        install_dir: util::install_dir()?,
+        is_installed: util::is_installed()?,
+        exe_matches: util::installed_exe_matches(),
+        shortcuts_valid: util::shortcuts_valid(),
+        shortcuts_exist: util::shortcuts_exist(),
+        install_date: util::installed_date(),
+    })
+}
+
+pub async fn fetch_latest(channel: Channel) -> Result<String> {
+    util::get_latest_version_for_channel(channel).await
+}
+
+/// Downloads a release and checks it against its published checksum, and its signature
+/// when built with the "signature" feature. A release that was already downloaded and
+/// verified comes from the cache instead.
+pub async fn download(
+    version: String,
+    os: Os,
+    arch: Arch,
+    mut on_progress: impl FnMut(ProgressEvent) -> ControlFlow<()>,
+) -> Result<(String, Vec<u8>), InstallError> {
+    if let Some(bytes) = util::cached_download(&version, os, arch) {
+        log::write(format!("Using the cached download of v{}", version));
+        let len = bytes.len() as u64;
+        let _ = on_progress(ProgressEvent::BytesDownloaded(Progress {
+            downloaded: len,
+            total: Some(len),
+            rate: None,
+        }));
+        return Ok((version, bytes));
+    }
+
+    let checksum = util::fetch_checksum(&version, os, arch).await?;
+    #[cfg(feature = "signature")]
+    let signature = if util::has_public_key() {
+        Some(util::fetch_signature(&version, os, arch).await?)
+    } else {
+        log::write("No release signing key built in, skipping the signature check");
+        None
+    };
+    let (version, bytes, hash) = util::download(version, os, arch, |progress| {
+        on_progress(ProgressEvent::BytesDownloaded(progress))
+    })
+    .await?;
+
+    if on_progress(ProgressEvent::Verifying).is_break() {
+        return Err(InstallError::Cancelled);
+    }
+    verify(&hash, &checksum)?;
+
+    #[cfg(feature = "signature")]
+    if let Some(signature) = signature {
+        util::verify_signature(&bytes, &signature)
+            .map_err(|e| InstallError::Signature(e.to_string()))?;
+        log::write("Signature verified");
+    }
+
+    // Not being able to cache it only means downloading it again next time
+    if let Err(e) = util::cache_download(&version, os, arch, &bytes, &hash) {
+        log::write(format!("Failed to cache the download: {}", e));
+    }
+
+    Ok((version, bytes))
+}
+
+/// Whether `download` checks release signatures. Without the "signature" feature, or
+/// before the signing key replaces the placeholder minisign.pub, it only has the checksum.
+pub fn checks_signature() -> bool {
+    #[cfg(feature = "signature")]
+    return util::has_public_key();
+    #[cfg(not(feature = "signature"))]
+    false
+}
+
+pub fn verify(hash: &str, expected: &str) -> Result<(), InstallError> {
+    util::verify_checksum(hash, expected)
+}
+
+/// Reads a release zip picked by the user, taking the version from its name
+pub fn read_release_file(path: &Path) -> Result<(String, Vec<u8>)> {
+    let version = util::version_from_zip_name(path)?;
+    let bytes = fs::read(path)?;
+
+    Ok((version, bytes))
+}
+
+/// What was installed where, and warnings about the steps that failed without failing the install
+pub async fn install(
+    version: String,
+    bytes: Vec<u8>,
+    install_dir: PathBuf,
+    options: InstallOptions,
+    on_progress: impl FnMut(ProgressEvent) -> ControlFlow<()>,
+) -> Result<InstallReport, InstallError> {
+    util::install(version, bytes, install_dir, options, on_progress).await
+}
+
+/// Re-downloads `version` and lays down the managed files, shortcuts and registry keys again,
+/// keeping the install's options and any user data
+pub async fn repair(
+    version: String,
+    os: Os,
+    arch: Arch,
+    mut on_progress: impl FnMut(ProgressEvent) -> ControlFlow<()>,
+) -> Result<String, InstallError> {
+    let install_dir = util::install_dir()?;
+    log::write(format!(
+        "Repairing v{} in {}",
+        version,
+        install_dir.display()
+    ));
+
+    let (version, bytes) = download(version, os, arch, &mut on_progress).await?;
+    let options =
+        util::installed_options(&install_dir, util::installed_scope().unwrap_or_default());
+    install(version, bytes, install_dir, options, on_progress)
+        .await
+        .map(|report| report.version)
+}
+
+/// How the installed exe compares to the release it claims to be
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verification {
+    Intact,
+    /// Changed, corrupted or missing
+    Modified,
+    /// The release or its checksum is gone from the server, e.g. for an old version
+    Unavailable,
+}
+
+/// Compares the installed exe against the one in the verified `version` release.
+/// The arch isn't recorded, so a v3 machine also tries the plain x86_64 build it may have fallen back to.
+pub async fn verify_installation(
+    version: String,
+    os: Os,
+    arch: Arch,
+) -> Result<Verification, InstallError> {
+    let Some(installed_hash) = util::installed_exe_hash()? else {
+        return Ok(Verification::Modified);
+    };
+
+    let archs: &[Arch] = match arch {
+        Arch::X86_64v3 => &[Arch::X86_64v3, Arch::X86_64],
+        _ => &[arch],
+    };
+
+    let mut found = false;
+    for &arch in archs {
+        let bytes = match download(version.clone(), os, arch, |_| ControlFlow::Continue(())).await {
+            Ok((_, bytes)) => bytes,
+            Err(InstallError::NotFound(name)) => {
+                log::write(format!(
+                    "Can't verify against {}, it's not on the server",
+                    name
+                ));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        found = true;
+
+        if util::archived_exe_hash(bytes)?.eq_ignore_ascii_case(&installed_hash) {
+            log::write(format!(
+                "The installed exe matches the v{} {} build",
+                version, arch
+            ));
+            return Ok(Verification::Intact);
+        }
+    }
+
+    Ok(if found {
+        Verification::Modified
+    } else {
+        Verification::Unavailable
+    })
+}
+
+pub fn uninstall(keep_user_data: bool) -> Result<(), InstallError> {
+    util::uninstall(util::is_uninstaller(), keep_user_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+    use std::{
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    /// Everything under download is blocking, so it's done by the first poll
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    fn cache(version: &str, bytes: &[u8]) {
+        let hash = Sha256::digest(bytes)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        util::cache_download(version, Os::Windows, Arch::X86_64, bytes, &hash).unwrap();
+    }
+
+    #[test]
+    fn second_download_comes_from_the_cache() {
+        // Not a real release, so it'd fail if it went to the network
+        let bytes = b"PK\x03\x04cached test release".to_vec();
+        cache("0.0.1-cache-test", &bytes);
+
+        let mut events = Vec::new();
+        let (version, downloaded) = block_on(download(
+            "0.0.1-cache-test".to_string(),
+            Os::Windows,
+            Arch::X86_64,
+            |event| {
+                events.push(event);
+                ControlFlow::Continue(())
+            },
+        ))
+        .unwrap();
+        assert_eq!(version, "0.0.1-cache-test");
+        assert_eq!(downloaded, bytes);
+        assert_eq!(
+            events,
+            [ProgressEvent::BytesDownloaded(Progress {
+                downloaded: bytes.len() as u64,
+                total: Some(bytes.len() as u64),
+                rate: None,
+            })]
+        );
+
+        // Only the latest version is kept
+        cache("0.0.2-cache-test", b"PK\x03\x04newer test release");
+        assert_eq!(
+            util::cached_download("0.0.1-cache-test", Os::Windows, Arch::X86_64),
+            None
+        );
+        assert!(util::cached_download("0.0.2-cache-test", Os::Windows, Arch::X86_64).is_some());
+        // Nor for another build of it
+        assert_eq!(
+            util::cached_download("0.0.2-cache-test", Os::Windows, Arch::Aarch64),
+            None
+        );
+
+        util::clear_cache().unwrap();
+        assert!(!util::has_cache());
+    }
+}
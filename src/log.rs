@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: 2026 Manuel Quarneti <mq1@ik.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use directories::BaseDirs;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use windows_sys::Win32::{Foundation::SYSTEMTIME, System::SystemInformation::GetLocalTime};
+
+/// How many lines to keep around for the error screen
+const MAX_LINES: usize = 200;
+
+/// The log is moved to install.log.1 once it grows past this
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+static LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+static FILE: OnceLock<Option<Mutex<File>>> = OnceLock::new();
+
+fn log_dir() -> Option<PathBuf> {
+    let base_dirs = BaseDirs::new()?;
+
+    Some(base_dirs.data_local_dir().join("TinyWiiBackupManager"))
+}
+
+fn log_path() -> Option<PathBuf> {
+    Some(log_dir()?.join("install.log"))
+}
+
+/// Opens the log file, rotating it first if it got too big
+fn open_file() -> Option<Mutex<File>> {
+    let path = log_path()?;
+    fs::create_dir_all(path.parent()?).ok()?;
+
+    if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > MAX_FILE_SIZE) {
+        let _ = fs::rename(&path, path.with_extension("log.1"));
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .ok()?;
+
+    Some(Mutex::new(file))
+}
+
+fn timestamp() -> String {
+    let mut now = SYSTEMTIME::default();
+    unsafe { GetLocalTime(&mut now) };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        now.wYear, now.wMonth, now.wDay, now.wHour, now.wMinute, now.wSecond
+    )
+}
+
+/// Records a step, both in memory and in install.log. Logging never fails the caller.
+pub fn write(message: impl AsRef<str>) {
+    let line = format!("[{}] {}", timestamp(), message.as_ref());
+
+    if let Some(file) = FILE.get_or_init(open_file)
+        && let Ok(mut file) = file.lock()
+    {
+        let _ = writeln!(file, "{}", line);
+    }
+
+    if let Ok(mut lines) = LINES.lock() {
+        if lines.len() == MAX_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+/// Writes a panic report to installer-crash.log, replacing the previous one.
+/// It never leaves the machine, the user can attach it to a bug report.
+pub fn write_crash_report(report: &str) -> Option<PathBuf> {
+    let path = log_dir()?.join("installer-crash.log");
+    fs::create_dir_all(path.parent()?).ok()?;
+
+    let mut contents = format!("[{}] {}\n\nLast log lines:\n", timestamp(), report);
+    for line in tail(50) {
+        contents.push_str(&line);
+        contents.push('\n');
+    }
+    fs::write(&path, contents).ok()?;
+
+    Some(path)
+}
+
+/// The last `n` lines logged by this run
+pub fn tail(n: usize) -> Vec<String> {
+    let Ok(lines) = LINES.lock() else {
+        return Vec::new();
+    };
+
+    lines
+        .iter()
+        .skip(lines.len().saturating_sub(n))
+        .cloned()
+        .collect()
+}
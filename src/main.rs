@@ -4,77 +4,1418 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+mod cli;
+mod i18n;
+mod installer;
+mod log;
 mod style;
 mod util;
 
-use crate::util::{Arch, Os};
+use crate::i18n::{Key, Lang, tr, trf};
+use crate::util::{
+    Arch, Channel, InstallAction, InstallError, InstallOptions, InstallReport, Os, Progress,
+    ProgressEvent, Scope, ThemePreference,
+};
 use iced::{
-    Alignment, Element, Length, Size, Task,
-    futures::TryFutureExt,
-    widget::{button, column, container, row, space, text},
+    Alignment, ContentFit, Element, Length, Point, Size, Subscription, Task, Theme,
+    futures::{SinkExt, Stream, TryFutureExt, channel::mpsc, executor::block_on},
+    keyboard::{self, key},
+    task,
+    widget::{
+        button, checkbox, column, container, image, pick_list, progress_bar, row, rule, scrollable,
+        space, text, text_input,
+    },
+    window,
+};
+use native_dialog::{DialogBuilder, MessageLevel};
+use std::{
+    backtrace::Backtrace,
+    env, mem,
+    ops::ControlFlow,
+    panic,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
 };
-use native_dialog::DialogBuilder;
-use std::path::PathBuf;
+
+const LOGO: &[u8] = include_bytes!("../assets/logo.png");
+
+const STAGES: [Key; 4] = [
+    Key::StageFetch,
+    Key::StageDownload,
+    Key::StageInstall,
+    Key::StageDone,
+];
+
+const SPINNER_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+
+/// Roomy enough for the advanced and network panels
+const WINDOW_SIZE: Size = Size::new(560.0, 520.0);
+
+/// What the window used to be, before it could be resized
+const MIN_WINDOW_SIZE: Size = Size::new(500.0, 460.0);
+
+/// About 12 fps, plenty for a text spinner
+const TICK: Duration = Duration::from_millis(80);
+
+/// What an install was started with, so a failed one can run again without a download
+struct InstallInputs {
+    version: String,
+    bytes: Vec<u8>,
+    install_dir: PathBuf,
+    options: InstallOptions,
+}
+
+/// Where "Try again" on the error screen picks up from
+enum Resume {
+    /// The screen the failed download (or the checks before it) started from,
+    /// to retry it or pick another build
+    Screen(Box<State>),
+    /// The failed install, and the screen to go back to if it's cancelled
+    Install(Box<InstallInputs>, Box<State>),
+}
 
 enum State {
-    FetchingLatestVersion,
-    GotLatestVersion(String),
-    Downloading(String),
-    Installing(String),
-    Installed(String),
+    /// Carries the install dir to keep, if one was picked
+    FetchingLatestVersion(Channel, Option<PathBuf>),
+    /// The first screen for a new user, a summary in front of the GotLatestVersion it holds
+    Welcome {
+        got_latest_version: Box<State>,
+        skip_welcome: bool,
+    },
+    CouldNotFetchLatestVersion(String),
+    /// Like CouldNotFetchLatestVersion, but with no connection at all
+    Offline {
+        is_installed: bool,
+        /// The installed version, if its download is cached so a repair works offline
+        cached_repair: Option<String>,
+    },
+    /// An overview for returning users, the full installer is one click away
+    Manage {
+        status: installer::Status,
+        /// None while it's being fetched
+        latest_version: Option<Result<String, String>>,
+        channel: Channel,
+        os: Os,
+        arch: Arch,
+        /// The installed options, kept by updates and repairs
+        options: InstallOptions,
+        skipped_version: Option<String>,
+        verifying: bool,
+        /// The last "Verify installation" result
+        verification: Option<Result<installer::Verification, String>>,
+    },
+    GotLatestVersion {
+        version: String,
+        /// The version to download, the latest one unless picked otherwise
+        selected_version: String,
+        versions: Vec<String>,
+        channel: Channel,
+        os: Os,
+        arch: Arch,
+        /// Whether there was no x86_64-v3 build and the plain x86_64 one is used instead
+        fell_back_from_v3: bool,
+        install_dir: PathBuf,
+        /// Why the last picked dir couldn't be used
+        install_dir_error: Option<String>,
+        /// Free space on the install dir's drive
+        free_space: Option<u64>,
+        build_number: Option<u32>,
+        /// What get_arch found, to warn when a build that won't run is picked
+        detected_arch: Arch,
+        release_notes: Option<String>,
+        installed_version: Option<String>,
+        /// Whether the install dir exists, even if the registry doesn't know the version
+        is_installed: bool,
+        /// The installed exe was replaced or corrupted since it was installed
+        exe_modified: bool,
+        /// A shortcut points somewhere else, e.g. since the install dir was moved
+        shortcuts_broken: bool,
+        show_network_settings: bool,
+        show_advanced: bool,
+        /// Whether there are downloads cached to clear
+        has_cache: bool,
+        /// What's typed in "Install a different version", if it's open
+        custom_version: Option<String>,
+        custom_version_error: Option<String>,
+        options: InstallOptions,
+    },
+    /// Carries the screen it was started from, to return to on cancel
+    Downloading(String, Progress, task::Handle, Box<State>),
+    /// Carries the kind of step that's running, once the first one started, and the flag
+    /// that asks the install thread to stop
+    Installing(
+        String,
+        Option<ProgressEvent>,
+        Arc<AtomicBool>,
+        Box<InstallInputs>,
+        Box<State>,
+    ),
+    /// The install was cancelled and is rolling back, carries the screen to go back to once
+    /// the install thread says it's done
+    CancellingInstall(Box<State>),
+    /// Includes the warnings about steps that failed without failing the install
+    Installed(InstallReport),
     InstalledPortable(String, PathBuf),
-    Errored(String),
+    /// Whether there's an install to offer uninstalling
+    UnsupportedOs(bool),
+    /// Whether we're running as uninstall.exe
+    AskingUninstallConfirmation(bool),
+    Uninstalling,
+    Uninstalled,
+    /// The picked install dir isn't writable without admin rights, or it's an all-users install
+    NeedsElevation(PathBuf, Scope, Box<State>),
+    /// What the install will change, and the Download to start once confirmed
+    ReviewingChanges(Vec<InstallAction>, Box<Message>, Box<State>),
+    /// TinyWiiBackupManager is running, carries the Download to retry once it's closed
+    AppRunning(Box<Message>, Box<State>),
+    /// On a metered connection, carries the Download to start if the user is fine with it
+    ConfirmingMetered(Box<Message>, Box<State>),
+    Errored {
+        error: InstallError,
+        /// The version we were trying to install, if any
+        version: Option<String>,
+        show_details: bool,
+        resume: Option<Resume>,
+    },
 }
 
 #[derive(Clone, Debug)]
 enum Message {
-    GotLatestVersion(Result<String, String>),
-    Download(String, Os, Arch),
-    Downloaded(Result<(String, Vec<u8>), String>),
-    Installed(Result<String, String>),
+    GotLatestVersion(Channel, Result<String, String>),
+    SelectChannel(Channel),
+    GotReleaseNotes(String, Result<String, String>),
+    GotVersions(Result<Vec<String>, String>),
+    SelectVersion(String),
+    SelectOs(Os),
+    SelectArch(Arch),
+    ChangeInstallDir,
+    SetInstallOptions(InstallOptions),
+    Download(String, Os, Arch, PathBuf, InstallOptions),
+    Progress(ProgressEvent),
+    Downloaded(
+        Result<(String, Vec<u8>), InstallError>,
+        PathBuf,
+        InstallOptions,
+    ),
+    Installed(Result<InstallReport, InstallError>),
     DownloadPortable(String, Os, Arch),
-    DownloadedPortable(Result<(String, PathBuf), String>),
+    DownloadedPortable(Result<(String, PathBuf), InstallError>),
+    Launch,
+    Launched(Result<(), InstallError>),
     LaunchTwbm,
     LaunchTwbmPortable(PathBuf),
+    OpenInstallFolder,
+    ShowInFolder(PathBuf),
+    InstallFromFile,
+    /// The release zip picked by InstallFromFile, then the install dir and options
+    InstallFile(PathBuf, PathBuf, InstallOptions),
+    ToggleNetworkSettings,
+    ToggleAdvanced,
+    CopyDownloadUrl(String),
+    ClearCache,
+    FixShortcuts,
+    ShowInstaller,
+    SkipVersion(String),
+    DismissWelcome,
+    SetSkipWelcome(bool),
+    VerifyInstallation(String, Os, Arch),
+    VerifiedInstallation(Result<installer::Verification, String>),
+    ProxyChanged(String),
+    MaxRateChanged(String),
+    Retry,
+    AskUninstall,
+    ReviewChanges(String, Os, Arch, PathBuf, InstallOptions),
+    ConfirmInstall,
+    RetryDownload,
+    CloseRunningApp,
+    ConfirmMetered,
+    Repair(String),
+    /// Whether to keep user data
+    Uninstall(bool),
+    Uninstalled(Result<(), InstallError>),
+    CancelUninstall(bool),
+    Close,
+    /// Back to where the error screen's Resume says
+    TryAgain,
+    /// The window's close button, which asks first while something is running
+    CloseRequested,
+    ConfirmExit,
+    RelaunchElevated(PathBuf, Scope),
+    SelectTheme(ThemePreference),
+    SelectLang(Lang),
+    ToggleDetails,
+    CopyErrorReport,
+    /// Opens a new GitHub issue with what the screen knows filled in
+    ReportProblem,
+    ToggleCustomVersion,
+    CustomVersionChanged(String),
+    InstallCustomVersion,
+    CheckedCustomVersion(Result<String, InstallError>),
+    Cancel,
+    WindowMoved(Point),
+    WindowResized(Size),
+    /// Enter, does what the screen's primary button does
+    Confirm,
+    /// Esc, backs out of a confirmation
+    Dismiss,
+    Tick,
 }
 
-impl State {
-    fn new() -> (Self, Task<Message>) {
-        let task = Task::perform(
-            util::get_latest_version().map_err(|e| e.to_string()),
-            Message::GotLatestVersion,
-        );
+/// The current screen, plus what stays around across screens
+struct App {
+    state: State,
+    theme_preference: ThemePreference,
+    theme: Theme,
+    lang: Lang,
+    /// Advanced by Message::Tick while waiting on the network
+    spinner_frame: usize,
+}
+
+impl App {
+    fn new(install_dir: Option<PathBuf>) -> (Self, Task<Message>) {
+        let (state, task) = State::new(install_dir);
+        let prefs = util::load_prefs();
+
+        let app = App {
+            state,
+            theme_preference: prefs.theme,
+            theme: resolve_theme(prefs.theme),
+            lang: prefs.lang.unwrap_or_else(Lang::detect),
+            spinner_frame: 0,
+        };
+
+        (app, task)
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::SelectTheme(theme_preference) => {
+                self.theme_preference = theme_preference;
+                self.theme = resolve_theme(theme_preference);
+
+                util::update_prefs(|prefs| prefs.theme = theme_preference);
+
+                Task::none()
+            }
+            Message::SelectLang(lang) => {
+                self.lang = lang;
+
+                util::update_prefs(|prefs| prefs.lang = Some(lang));
+
+                Task::none()
+            }
+            Message::CloseRequested => {
+                if !self.state.is_busy() {
+                    return iced::exit();
+                }
+
+                let confirmed = DialogBuilder::message()
+                    .set_level(MessageLevel::Warning)
+                    .set_title(tr(Key::ConfirmExitTitle, self.lang))
+                    .set_text(tr(Key::ConfirmExit, self.lang))
+                    .confirm()
+                    .show()
+                    .unwrap_or_default();
+
+                if confirmed {
+                    Task::done(Message::ConfirmExit)
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Tick => {
+                self.spinner_frame = (self.spinner_frame + 1) % SPINNER_FRAMES.len();
+                Task::none()
+            }
+            // Minimizing moves the window off to -32000
+            Message::WindowMoved(position) => {
+                if util::is_on_screen(position.x as i32, position.y as i32) {
+                    util::update_prefs(|prefs| {
+                        prefs.window_position = Some((position.x as i32, position.y as i32))
+                    });
+                }
+
+                Task::none()
+            }
+            Message::WindowResized(size) => {
+                if size.width >= MIN_WINDOW_SIZE.width && size.height >= MIN_WINDOW_SIZE.height {
+                    util::update_prefs(|prefs| {
+                        prefs.window_size = Some((size.width as u32, size.height as u32))
+                    });
+                }
 
-        (State::FetchingLatestVersion, task)
+                Task::none()
+            }
+            message => self.state.update(message, self.lang),
+        }
+    }
+
+    /// Follows the language picked
+    fn title(&self) -> String {
+        tr(Key::WindowTitle, self.lang).to_string()
+    }
+
+    /// Ticks only while there's a spinner on screen, keys and window moves are always listened to
+    fn subscription(&self) -> Subscription<Message> {
+        let spinner = match self.state {
+            State::FetchingLatestVersion(..)
+            | State::Manage {
+                latest_version: None,
+                ..
+            }
+            | State::Downloading(
+                _,
+                Progress {
+                    total: None | Some(0),
+                    ..
+                },
+                _,
+                _,
+            ) => Subscription::run(ticks),
+            _ => Subscription::none(),
+        };
+
+        Subscription::batch([spinner, keyboard_shortcuts(), window_changes()])
     }
 
     fn view(&self) -> Element<'_, Message> {
+        column![
+            row![
+                image(image::Handle::from_bytes(LOGO))
+                    .height(32)
+                    .content_fit(ContentFit::Contain),
+                text("TinyWiiBackupManager").size(16),
+                space::horizontal(),
+                pick_list(Lang::ALL, Some(self.lang), Message::SelectLang)
+                    .text_size(12)
+                    .style(style::rounded_pick_list),
+                pick_list(
+                    ThemePreference::ALL,
+                    Some(self.theme_preference),
+                    Message::SelectTheme
+                )
+                .text_size(12)
+                .style(style::rounded_pick_list),
+            ]
+            .spacing(10)
+            .padding(5)
+            .align_y(Alignment::Center),
+            self.state.stage().map(|stage| stepper(stage, self.lang)),
+            container(
+                self.state
+                    .view(self.lang, SPINNER_FRAMES[self.spinner_frame])
+            )
+            .center(Length::Fill),
+        ]
+        .align_x(Alignment::Center)
+        .into()
+    }
+
+    fn theme(&self) -> Theme {
+        self.theme.clone()
+    }
+}
+
+fn resolve_theme(theme_preference: ThemePreference) -> Theme {
+    let high_contrast = theme_preference.is_high_contrast();
+    style::set_high_contrast(high_contrast);
+
+    if high_contrast {
+        style::high_contrast_theme()
+    } else if theme_preference.is_light() {
+        Theme::Light
+    } else {
+        Theme::Dark
+    }
+}
+
+impl State {
+    fn new(install_dir: Option<PathBuf>) -> (Self, Task<Message>) {
+        if util::is_uninstaller() {
+            return (State::AskingUninstallConfirmation(true), Task::none());
+        }
+
+        if let Err(e) = util::get_os() {
+            log::write(e.to_string());
+            let is_installed = util::is_installed().unwrap_or(false);
+            return (State::UnsupportedOs(is_installed), Task::none());
+        }
+
+        let prefs = util::load_prefs();
+        let channel = prefs.channel;
+
+        // Unless a dir was passed along (e.g. by an elevated relaunch) to install into
+        if install_dir.is_none()
+            && let Ok(status) = installer::status()
+            && status.is_installed
+        {
+            return (
+                State::manage(status, channel),
+                fetch_latest_version(channel),
+            );
+        }
+
+        // An explicit --install-dir wins over the remembered one
+        let install_dir = install_dir.or(prefs.install_dir);
+
+        (
+            State::FetchingLatestVersion(channel, install_dir),
+            fetch_latest_version(channel),
+        )
+    }
+
+    fn offline() -> Self {
+        let status = installer::status()
+            .ok()
+            .filter(|status| status.is_installed);
+        let (os, arch) = (util::get_os().unwrap_or_default(), util::get_arch());
+
+        State::Offline {
+            is_installed: status.is_some(),
+            cached_repair: status
+                .and_then(|status| status.installed_version)
+                .filter(|version| util::cached_download(version, os, arch).is_some()),
+        }
+    }
+
+    fn manage(status: installer::Status, channel: Channel) -> Self {
+        let scope = util::installed_scope().unwrap_or_default();
+
+        State::Manage {
+            options: util::installed_options(&status.install_dir, scope),
+            status,
+            latest_version: None,
+            channel,
+            os: util::get_os().unwrap_or_default(),
+            arch: util::get_arch(),
+            skipped_version: util::load_prefs().skipped_version,
+            verifying: false,
+            verification: None,
+        }
+    }
+
+    /// The version picker, preselecting the detected OS and arch
+    fn got_latest_version(version: String, channel: Channel, install_dir: Option<PathBuf>) -> Self {
+        let os = util::get_os().unwrap_or_default();
+        let arch = util::get_arch();
+        let status = installer::status().ok();
+        let options = util::load_prefs().options;
+        let install_dir = install_dir
+            .or_else(|| {
+                status
+                    .as_ref()
+                    .filter(|status| status.is_installed)
+                    .map(|status| status.install_dir.clone())
+            })
+            .or_else(|| util::default_install_dir(options.scope).ok())
+            .unwrap_or_default();
+
+        State::GotLatestVersion {
+            selected_version: version.clone(),
+            versions: vec![version.clone()],
+            version,
+            channel,
+            os,
+            arch,
+            fell_back_from_v3: false,
+            free_space: util::free_space(&install_dir).ok(),
+            install_dir,
+            install_dir_error: None,
+            build_number: util::get_build_number().ok(),
+            detected_arch: arch,
+            release_notes: None,
+            is_installed: status.as_ref().is_some_and(|status| status.is_installed),
+            exe_modified: status
+                .as_ref()
+                .is_some_and(|status| status.exe_matches == Some(false)),
+            shortcuts_broken: status
+                .as_ref()
+                .is_some_and(|status| status.is_installed && !status.shortcuts_valid),
+            installed_version: status.and_then(|status| status.installed_version),
+            show_network_settings: false,
+            show_advanced: false,
+            has_cache: util::has_cache(),
+            custom_version: None,
+            custom_version_error: None,
+            options,
+        }
+    }
+
+    fn fail(&mut self, error: InstallError) {
+        log::write(format!("Error: {}", error));
+
+        let version = self.target_version();
+        let resume =
+            match mem::replace(self, State::FetchingLatestVersion(Channel::default(), None)) {
+                State::Installing(.., inputs, previous) => Some(Resume::Install(inputs, previous)),
+                State::Downloading(.., previous)
+                | State::CancellingInstall(previous)
+                | State::NeedsElevation(_, _, previous)
+                | State::ReviewingChanges(_, _, previous)
+                | State::AppRunning(_, previous)
+                | State::ConfirmingMetered(_, previous) => Some(Resume::Screen(previous)),
+                state @ (State::GotLatestVersion { .. } | State::Manage { .. }) => {
+                    Some(Resume::Screen(Box::new(state)))
+                }
+                _ => None,
+            };
+
+        *self = State::Errored {
+            error,
+            version,
+            show_details: false,
+            resume,
+        };
+    }
+
+    /// Where we are in STAGES, if it's part of the install flow at all
+    fn stage(&self) -> Option<usize> {
+        match self {
+            State::FetchingLatestVersion(..)
+            | State::Welcome { .. }
+            | State::CouldNotFetchLatestVersion(_)
+            | State::Offline { .. }
+            | State::GotLatestVersion { .. } => Some(0),
+            State::Downloading(..) => Some(1),
+            // Nothing started yet, so still wherever they were opened from
+            State::NeedsElevation(_, _, previous)
+            | State::ReviewingChanges(_, _, previous)
+            | State::AppRunning(_, previous)
+            | State::ConfirmingMetered(_, previous) => previous.stage(),
+            State::Installing(..) | State::CancellingInstall(_) => Some(2),
+            State::Installed(..) | State::InstalledPortable(..) => Some(3),
+            _ => None,
+        }
+    }
+
+    fn target_version(&self) -> Option<String> {
+        match self {
+            // A valid custom version is what Install would go for, else the picked one
+            State::GotLatestVersion {
+                selected_version,
+                custom_version,
+                ..
+            } => custom_version
+                .as_deref()
+                .and_then(|input| util::parse_version_input(input).ok())
+                .or_else(|| Some(selected_version.clone())),
+            State::Downloading(version, ..)
+            | State::Installing(version, ..)
+            | State::InstalledPortable(version, _) => Some(version.clone()),
+            State::Installed(report) => Some(report.version.clone()),
+            State::NeedsElevation(_, _, previous)
+            | State::ReviewingChanges(_, _, previous)
+            | State::AppRunning(_, previous)
+            | State::ConfirmingMetered(_, previous) => previous.target_version(),
+            _ => None,
+        }
+    }
+
+    /// Whether the running download is for an x86_64-v3 build picked on the main screen
+    fn is_downloading_v3(&self) -> bool {
+        matches!(
+            self,
+            State::Downloading(.., previous) if matches!(
+                **previous,
+                State::GotLatestVersion {
+                    arch: Arch::X86_64v3,
+                    ..
+                } | State::Manage {
+                    arch: Arch::X86_64v3,
+                    ..
+                }
+            )
+        )
+    }
+
+    /// Whether closing the window now would interrupt a download, install or uninstall
+    fn is_busy(&self) -> bool {
+        matches!(
+            self,
+            State::Downloading(..)
+                | State::Installing(..)
+                | State::CancellingInstall(_)
+                | State::Uninstalling
+        )
+    }
+
+    /// What Enter does, the same as the screen's primary button
+    fn default_action(&self) -> Option<Message> {
+        match self {
+            State::Welcome { .. } => Some(Message::DismissWelcome),
+            State::GotLatestVersion {
+                selected_version,
+                os,
+                arch,
+                install_dir,
+                free_space,
+                options,
+                ..
+            } => free_space
+                .is_none_or(|free| free >= util::REQUIRED_SPACE)
+                .then(|| {
+                    Message::ReviewChanges(
+                        selected_version.clone(),
+                        *os,
+                        *arch,
+                        install_dir.clone(),
+                        *options,
+                    )
+                }),
+            State::Manage {
+                status,
+                latest_version,
+                os,
+                arch,
+                options,
+                skipped_version,
+                ..
+            } => Some(
+                match available_update(status, latest_version)
+                    .filter(|latest| Some(*latest) != skipped_version.as_ref())
+                {
+                    Some(latest) => Message::ReviewChanges(
+                        latest.clone(),
+                        *os,
+                        *arch,
+                        status.install_dir.clone(),
+                        *options,
+                    ),
+                    None => Message::Launch,
+                },
+            ),
+            // Deleting user data too takes a click
+            State::AskingUninstallConfirmation(_) => Some(Message::Uninstall(true)),
+            State::NeedsElevation(install_dir, scope, _) => {
+                Some(Message::RelaunchElevated(install_dir.clone(), *scope))
+            }
+            State::ReviewingChanges(..) => Some(Message::ConfirmInstall),
+            State::AppRunning(..) => Some(Message::RetryDownload),
+            State::ConfirmingMetered(..) => Some(Message::ConfirmMetered),
+            State::Installed(..) if util::is_dry_run() => Some(Message::Close),
+            State::Installed(..) => Some(Message::LaunchTwbm),
+            State::Uninstalled => Some(Message::Close),
+            _ => None,
+        }
+    }
+
+    /// What Esc does, only on screens that ask before going on
+    fn dismiss_action(&self) -> Option<Message> {
+        match self {
+            State::AskingUninstallConfirmation(is_uninstaller) => {
+                Some(Message::CancelUninstall(*is_uninstaller))
+            }
+            State::NeedsElevation(..)
+            | State::ReviewingChanges(..)
+            | State::AppRunning(..)
+            | State::ConfirmingMetered(..) => Some(Message::Cancel),
+            _ => None,
+        }
+    }
+
+    /// Where an install started from this screen goes and with what options. Off the
+    /// install screens, that's the existing install with the options it was made with.
+    fn install_target(&self) -> anyhow::Result<(PathBuf, InstallOptions)> {
+        match self {
+            State::GotLatestVersion {
+                install_dir,
+                options,
+                ..
+            } => Ok((install_dir.clone(), *options)),
+            State::Manage {
+                status, options, ..
+            } => Ok((status.install_dir.clone(), *options)),
+            _ => {
+                let install_dir = util::install_dir()?;
+                let scope = util::installed_scope().unwrap_or_default();
+                let options = util::installed_options(&install_dir, scope);
+                Ok((install_dir, options))
+            }
+        }
+    }
+
+    /// Switches to the screen that has to come first when the install can't start yet,
+    /// asking for admin rights or to close TinyWiiBackupManager. `retry` runs once it's sorted.
+    fn blocked_install(
+        &mut self,
+        install_dir: &Path,
+        options: InstallOptions,
+        retry: Message,
+    ) -> bool {
+        if !util::can_write_to(install_dir)
+            || (options.scope == Scope::AllUsers && !options.portable && !util::is_elevated())
+        {
+            let previous = self.take_previous();
+            *self = State::NeedsElevation(install_dir.to_path_buf(), options.scope, previous);
+            return true;
+        }
+
+        // Its files would be locked, so ask to close it first
+        if util::is_running() {
+            let previous = self.take_previous();
+            *self = State::AppRunning(Box::new(retry), previous);
+            return true;
+        }
+
+        false
+    }
+
+    /// Takes the screen to return to if the operation about to start gets cancelled
+    fn take_previous(&mut self) -> Box<State> {
+        match mem::replace(self, State::FetchingLatestVersion(Channel::default(), None)) {
+            State::Downloading(.., previous)
+            | State::Installing(.., previous)
+            | State::CancellingInstall(previous)
+            | State::NeedsElevation(_, _, previous)
+            | State::ReviewingChanges(_, _, previous)
+            | State::AppRunning(_, previous)
+            | State::ConfirmingMetered(_, previous) => previous,
+            state => Box::new(state),
+        }
+    }
+
+    fn start_download(
+        &mut self,
+        version: String,
+        os: Os,
+        arch: Arch,
+        install_dir: PathBuf,
+        options: InstallOptions,
+    ) -> Task<Message> {
+        let (task, handle) = run_in_thread({
+            let version = version.clone();
+            move |mut tx| {
+                let res = block_on(installer::download(version, os, arch, |progress| {
+                    report_progress(&mut tx, progress)
+                }));
+
+                let _ = block_on(tx.send(Message::Downloaded(res, install_dir, options)));
+            }
+        })
+        .abortable();
+
+        let previous = self.take_previous();
+        *self = State::Downloading(version, Progress::default(), handle, previous);
+        task
+    }
+
+    fn start_install(
+        &mut self,
+        version: String,
+        bytes: Vec<u8>,
+        install_dir: PathBuf,
+        options: InstallOptions,
+    ) -> Task<Message> {
+        let inputs = Box::new(InstallInputs {
+            version: version.clone(),
+            bytes: bytes.clone(),
+            install_dir: install_dir.clone(),
+            options,
+        });
+        let cancel = Arc::new(AtomicBool::new(false));
+        let task = run_in_thread({
+            let version = version.clone();
+            let cancel = cancel.clone();
+            move |mut tx| {
+                let res = block_on(installer::install(
+                    version,
+                    bytes,
+                    install_dir.clone(),
+                    options,
+                    |event| {
+                        if cancel.load(Ordering::Relaxed) {
+                            return ControlFlow::Break(());
+                        }
+                        report_progress(&mut tx, event)
+                    },
+                ));
+
+                // A portable install ends up like a saved portable exe, with nothing to register
+                let message = match res {
+                    Ok(report) if options.portable => Message::DownloadedPortable(Ok((
+                        report.version,
+                        install_dir.join("TinyWiiBackupManager.exe"),
+                    ))),
+                    res => Message::Installed(res),
+                };
+
+                // Not aborted on cancel, so the rollback is over once this arrives
+                let _ = block_on(tx.send(message));
+            }
+        });
+
+        let previous = self.take_previous();
+        *self = State::Installing(version, None, cancel, inputs, previous);
+        task
+    }
+
+    fn view(&self, lang: Lang, spinner: &'static str) -> Element<'_, Message> {
         let content: Element<'_, Message> = match self {
-            State::FetchingLatestVersion => text("Fetching latest version...").into(),
-            State::GotLatestVersion(version) => {
-                let os = util::get_os().unwrap_or_default();
-                let arch = util::get_arch();
-                let is_installed = util::is_installed().unwrap_or(false);
-                let install_str = match is_installed {
-                    true => "Update/Reinstall",
-                    false => "Download and Install",
+            State::FetchingLatestVersion(channel, _) => match channel {
+                Channel::Stable => text(trf(Key::FetchingLatest, lang, &[&spinner])),
+                Channel::Prerelease => text(trf(Key::FetchingLatestPrerelease, lang, &[&spinner])),
+            }
+            .into(),
+            State::Manage {
+                status,
+                latest_version,
+                os,
+                arch,
+                options,
+                skipped_version,
+                verifying,
+                verification,
+                ..
+            } => {
+                let latest = available_update(status, latest_version);
+                // A skipped update can still be installed, it just isn't pushed
+                let update = latest.filter(|latest| Some(*latest) != skipped_version.as_ref());
+                let update_status = match latest_version {
+                    None => trf(Key::CheckingForUpdates, lang, &[&spinner]),
+                    Some(Err(_)) => tr(Key::CouldNotCheckForUpdates, lang).to_string(),
+                    Some(Ok(_)) => match (update, latest) {
+                        (Some(latest), _) => trf(Key::UpdateAvailable, lang, &[latest]),
+                        (None, Some(skipped)) => trf(Key::VersionSkipped, lang, &[skipped]),
+                        (None, None) => tr(Key::UpToDate, lang).to_string(),
+                    },
+                };
+                let shortcuts_status = if !status.shortcuts_valid {
+                    Some(Key::ShortcutsBroken)
+                } else if !status.shortcuts_exist {
+                    Some(Key::ShortcutsMissing)
+                } else {
+                    None
+                };
+
+                column![
+                    text(tr(Key::AlreadyInstalled, lang)).size(16),
+                    status
+                        .installed_version
+                        .as_ref()
+                        .map(|installed| text(installed_version_text(installed, lang))),
+                    text(trf(Key::Location, lang, &[&status.install_dir.display()])).size(12),
+                    status.install_date.as_ref().map(|date| text(trf(
+                        Key::InstalledOn,
+                        lang,
+                        &[date]
+                    ))
+                    .size(12)),
+                    match shortcuts_status {
+                        Some(key) => row![
+                            text(tr(key, lang)).size(12).style(text::warning),
+                            button(text(tr(Key::FixShortcuts, lang)).size(12))
+                                .style(style::rounded_secondary_button)
+                                .on_press(Message::FixShortcuts),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                        None => row![text(tr(Key::ShortcutsOk, lang)).size(12)],
+                    },
+                    status
+                        .installed_version
+                        .as_ref()
+                        .filter(|_| status.exe_matches == Some(false))
+                        .map(
+                            |installed| text(trf(Key::FilesModified, lang, &[installed]))
+                                .size(12)
+                                .style(text::danger)
+                        ),
+                    row![
+                        text(update_status).size(12),
+                        update.map(|latest| button(text(tr(Key::SkipVersion, lang)).size(12))
+                            .style(button::text)
+                            .on_press(Message::SkipVersion(latest.clone()))),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    row![
+                        latest.map(|latest| button(text(trf(Key::Update, lang, &[latest])))
+                            .style(if update.is_some() {
+                                style::rounded_button
+                            } else {
+                                style::rounded_secondary_button
+                            })
+                            .on_press(Message::ReviewChanges(
+                                latest.clone(),
+                                *os,
+                                *arch,
+                                status.install_dir.clone(),
+                                *options
+                            ))),
+                        button(tr(Key::Launch, lang))
+                            .style(if update.is_some() {
+                                style::rounded_secondary_button
+                            } else {
+                                style::rounded_button
+                            })
+                            .on_press(Message::Launch),
+                        status.installed_version.as_ref().map(|installed| button(tr(
+                            Key::RepairInstallation,
+                            lang
+                        ))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Repair(installed.clone()))),
+                        button(tr(Key::Uninstall, lang))
+                            .style(style::rounded_secondary_button)
+                            .on_press(Message::AskUninstall),
+                    ]
+                    .spacing(10),
+                    status.installed_version.as_ref().and_then(|installed| {
+                        let (key, style): (_, fn(&Theme) -> text::Style) = match verification {
+                            Some(Ok(installer::Verification::Intact)) => {
+                                (Key::VerifyIntact, text::success)
+                            }
+                            Some(Ok(installer::Verification::Modified)) => {
+                                (Key::VerifyModified, text::danger)
+                            }
+                            Some(Ok(installer::Verification::Unavailable)) => {
+                                (Key::VerifyUnavailable, text::warning)
+                            }
+                            Some(Err(e)) => {
+                                return Some(
+                                    text(trf(Key::VerifyFailed, lang, &[e]))
+                                        .size(12)
+                                        .style(text::danger),
+                                );
+                            }
+                            None => return None,
+                        };
+                        Some(text(trf(key, lang, &[installed])).size(12).style(style))
+                    }),
+                    row![
+                        status.installed_version.as_ref().map(|installed| button(
+                            text(tr(
+                                if *verifying {
+                                    Key::Verifying
+                                } else {
+                                    Key::VerifyInstallation
+                                },
+                                lang
+                            ))
+                            .size(12)
+                        )
+                        .style(button::text)
+                        .on_press_maybe((!*verifying).then(|| Message::VerifyInstallation(
+                            installed.clone(),
+                            *os,
+                            *arch
+                        )))),
+                        button(text(tr(Key::MoreOptions, lang)).size(12))
+                            .style(button::text)
+                            .on_press(Message::ShowInstaller),
+                        button(text(tr(Key::ReportProblem, lang)).size(12))
+                            .style(button::text)
+                            .on_press(Message::ReportProblem),
+                    ]
+                    .spacing(10),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center)
+                .into()
+            }
+            State::Welcome {
+                got_latest_version,
+                skip_welcome,
+            } => {
+                let summary = match got_latest_version.as_ref() {
+                    State::GotLatestVersion {
+                        selected_version,
+                        install_dir,
+                        ..
+                    } => trf(
+                        Key::WelcomeSummary,
+                        lang,
+                        &[selected_version, &install_dir.display()],
+                    ),
+                    _ => String::new(),
                 };
 
                 column![
-                    text(format!("Latest version: v{}", version)),
-                    text(format!("Detected OS: {}", os.as_display_str())),
-                    text(format!("Detected arch: {}", arch.as_display_str())),
-                    space(),
-                    space(),
-                    space(),
-                    space(),
+                    text(tr(Key::Welcome, lang)).size(20),
+                    text(summary).size(12),
+                    button(text(tr(Key::Continue, lang)).size(16))
+                        .style(style::rounded_button)
+                        .padding([10, 40])
+                        .on_press(Message::DismissWelcome),
+                    checkbox(*skip_welcome)
+                        .label(tr(Key::DontShowAgain, lang))
+                        .text_size(12)
+                        .on_toggle(Message::SetSkipWelcome),
+                ]
+                .spacing(15)
+                .align_x(Alignment::Center)
+                .into()
+            }
+            State::Offline {
+                is_installed,
+                cached_repair,
+            } => column![
+                text(tr(Key::Offline, lang)),
+                text(tr(Key::OfflineHint, lang)).size(12),
+                row![
+                    button(tr(Key::Retry, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::Retry),
+                    is_installed.then(|| button(tr(Key::Launch, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Launch)),
+                    cached_repair
+                        .as_ref()
+                        .map(|version| button(tr(Key::RepairInstallation, lang))
+                            .style(style::rounded_secondary_button)
+                            .on_press(Message::Repair(version.clone()))),
+                    is_installed.then(|| button(tr(Key::Uninstall, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::AskUninstall)),
+                ]
+                .spacing(10),
+                button(text(tr(Key::InstallFromFile, lang)).size(12))
+                    .style(button::text)
+                    .on_press(Message::InstallFromFile),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::CouldNotFetchLatestVersion(msg) => column![
+                text(trf(Key::CouldNotFetch, lang, &[msg])),
+                network_settings(lang),
+                row![
+                    button(tr(Key::Retry, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::Retry),
+                    button(tr(Key::InstallFromFile, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::InstallFromFile),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::GotLatestVersion {
+                version,
+                selected_version,
+                versions,
+                channel,
+                os,
+                arch,
+                fell_back_from_v3,
+                install_dir,
+                install_dir_error,
+                free_space,
+                build_number,
+                detected_arch,
+                release_notes,
+                installed_version,
+                is_installed,
+                exe_modified,
+                shortcuts_broken,
+                show_network_settings,
+                show_advanced,
+                has_cache,
+                custom_version,
+                custom_version_error,
+                options,
+            } => {
+                let detected_os = match build_number {
+                    Some(build_number) => trf(
+                        Key::Detected,
+                        lang,
+                        &[&util::os_from_build_number(*build_number), build_number],
+                    ),
+                    None => tr(Key::DetectedUnknown, lang).to_string(),
+                };
+                // If we can't tell, let the install try
+                let has_space = free_space.is_none_or(|free| free >= util::REQUIRED_SPACE);
+                let install_str = match installed_version {
+                    Some(installed) if installed == selected_version => {
+                        trf(Key::Reinstall, lang, &[selected_version])
+                    }
+                    Some(installed) if !util::is_newer(selected_version, installed) => {
+                        trf(Key::Downgrade, lang, &[selected_version])
+                    }
+                    Some(_) => trf(Key::Update, lang, &[selected_version]),
+                    None if *is_installed => trf(Key::Update, lang, &[selected_version]),
+                    None if selected_version != version => {
+                        trf(Key::DownloadAndInstallVersion, lang, &[selected_version])
+                    }
+                    None => tr(Key::DownloadAndInstall, lang).to_string(),
+                };
+
+                column![
+                    row![
+                        text(trf(Key::LatestVersion, lang, &[version])),
+                        pick_list(
+                            versions.as_slice(),
+                            Some(selected_version),
+                            Message::SelectVersion
+                        )
+                        .style(style::rounded_pick_list),
+                        pick_list(Channel::ALL, Some(*channel), Message::SelectChannel)
+                            .style(style::rounded_pick_list),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    installed_version.as_ref().map(|installed| row![
+                        text(installed_version_text(installed, lang)),
+                        button(text(tr(Key::Uninstall, lang)).size(12))
+                            .style(button::text)
+                            .on_press(Message::AskUninstall),
+                        is_installed.then(|| button(
+                            text(tr(Key::RepairInstallation, lang)).size(12)
+                        )
+                        .style(button::text)
+                        .on_press(Message::Repair(installed.clone()))),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)),
+                    installed_version
+                        .as_ref()
+                        .filter(|_| *exe_modified)
+                        .map(
+                            |installed| text(trf(Key::FilesModified, lang, &[installed]))
+                                .size(12)
+                                .style(text::danger)
+                        ),
+                    shortcuts_broken.then(|| row![
+                        text(tr(Key::ShortcutsBroken, lang))
+                            .size(12)
+                            .style(text::warning),
+                        button(text(tr(Key::FixShortcuts, lang)).size(12))
+                            .style(style::rounded_secondary_button)
+                            .on_press(Message::FixShortcuts),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)),
+                    row![
+                        text(tr(Key::Os, lang)),
+                        pick_list(Os::ALL, Some(*os), Message::SelectOs)
+                            .style(style::rounded_pick_list),
+                        text(detected_os).size(12),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    row![
+                        text(tr(Key::Arch, lang)),
+                        pick_list(Arch::ALL, Some(*arch), Message::SelectArch)
+                            .style(style::rounded_pick_list),
+                        fell_back_from_v3.then(|| text(tr(Key::NoV3Build, lang)).size(12)),
+                        (!arch.is_compatible_with(*detected_arch)).then(|| text(tr(
+                            Key::ArchIncompatible,
+                            lang
+                        ))
+                        .size(12)
+                        .style(text::warning)),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    row![
+                        text(trf(Key::InstallTo, lang, &[&install_dir.display()])).size(12),
+                        free_space.map(|free| text(trf(
+                            Key::FreeSpace,
+                            lang,
+                            &[&format_size(free)]
+                        ))
+                        .size(12)
+                        .style(if has_space {
+                            text::default
+                        } else {
+                            text::danger
+                        })),
+                        button(text(tr(Key::Change, lang)).size(12))
+                            .style(style::rounded_secondary_button)
+                            .on_press(Message::ChangeInstallDir),
+                        pick_list(Scope::ALL, Some(options.scope), |scope| {
+                            Message::SetInstallOptions(InstallOptions { scope, ..*options })
+                        })
+                        .text_size(12)
+                        .style(style::rounded_pick_list),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    install_dir_error
+                        .as_deref()
+                        .map(|e| text(e).size(12).style(text::danger)),
+                    (!has_space).then(|| text(trf(
+                        Key::NotEnoughSpace,
+                        lang,
+                        &[&format_size(util::REQUIRED_SPACE)]
+                    ))
+                    .size(12)
+                    .style(text::danger)),
+                    release_notes
+                        .as_deref()
+                        .map(|notes| scrollable(text(notes).size(12))
+                            .height(70)
+                            .width(Length::Fill)),
+                    row![
+                        button(
+                            text(format!(
+                                "{} {}",
+                                tr(Key::NetworkSettings, lang),
+                                if *show_network_settings { "▴" } else { "▾" }
+                            ))
+                            .size(12)
+                        )
+                        .style(button::text)
+                        .on_press(Message::ToggleNetworkSettings),
+                        button(text(tr(Key::InstallDifferentVersion, lang)).size(12))
+                            .style(button::text)
+                            .on_press(Message::ToggleCustomVersion),
+                        button(
+                            text(format!(
+                                "{} {}",
+                                tr(Key::Advanced, lang),
+                                if *show_advanced { "▴" } else { "▾" }
+                            ))
+                            .size(12)
+                        )
+                        .style(button::text)
+                        .on_press(Message::ToggleAdvanced),
+                    ],
+                    show_network_settings.then(|| network_settings(lang)),
+                    show_advanced.then(|| {
+                        let url = util::download_url(selected_version, *os, *arch);
+
+                        column![
+                            row![
+                                text(tr(Key::DownloadUrl, lang)).size(12),
+                                text(url.clone()).size(12),
+                                button(text(tr(Key::Copy, lang)).size(12))
+                                    .style(style::rounded_secondary_button)
+                                    .on_press(Message::CopyDownloadUrl(url)),
+                            ]
+                            .spacing(10)
+                            .align_y(Alignment::Center),
+                            button(text(tr(Key::ClearCache, lang)).size(12))
+                                .style(style::rounded_secondary_button)
+                                .on_press_maybe(has_cache.then_some(Message::ClearCache)),
+                        ]
+                        .spacing(5)
+                        .align_x(Alignment::Center)
+                    }),
+                    custom_version.as_deref().map(|custom_version| row![
+                        text_input("1.2.3", custom_version)
+                            .on_input(Message::CustomVersionChanged)
+                            .on_submit(Message::InstallCustomVersion)
+                            .size(12),
+                        button(text(tr(Key::Install, lang)).size(12))
+                            .style(style::rounded_secondary_button)
+                            .on_press(Message::InstallCustomVersion),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)),
+                    custom_version_error
+                        .as_deref()
+                        .map(|e| text(e).size(12).style(text::danger)),
+                    (!options.portable).then(|| row![
+                        checkbox(options.pin_to_taskbar)
+                            .label(tr(Key::PinToTaskbar, lang))
+                            .text_size(12)
+                            .on_toggle_maybe(options.start_menu_shortcut.then_some(
+                                |pin_to_taskbar| Message::SetInstallOptions(InstallOptions {
+                                    pin_to_taskbar,
+                                    ..*options
+                                })
+                            )),
+                        checkbox(options.add_to_path)
+                            .label(tr(Key::AddToPath, lang))
+                            .text_size(12)
+                            .on_toggle(|add_to_path| Message::SetInstallOptions(InstallOptions {
+                                add_to_path,
+                                ..*options
+                            })),
+                        checkbox(options.associate_files)
+                            .label(tr(Key::OpenWbfs, lang))
+                            .text_size(12)
+                            .on_toggle(|associate_files| Message::SetInstallOptions(
+                                InstallOptions {
+                                    associate_files,
+                                    associate_iso: associate_files && options.associate_iso,
+                                    ..*options
+                                }
+                            )),
+                        checkbox(options.associate_iso)
+                            .label(tr(Key::IsoToo, lang))
+                            .text_size(12)
+                            .on_toggle_maybe(options.associate_files.then_some(|associate_iso| {
+                                Message::SetInstallOptions(InstallOptions {
+                                    associate_iso,
+                                    ..*options
+                                })
+                            })),
+                    ]
+                    .spacing(10)),
+                    (!options.portable).then(|| row![
+                        checkbox(options.desktop_shortcut)
+                            .label(tr(Key::DesktopShortcut, lang))
+                            .text_size(12)
+                            .on_toggle(|desktop_shortcut| Message::SetInstallOptions(
+                                InstallOptions {
+                                    desktop_shortcut,
+                                    ..*options
+                                }
+                            )),
+                        checkbox(options.start_menu_shortcut)
+                            .label(tr(Key::StartMenuShortcut, lang))
+                            .text_size(12)
+                            .on_toggle(|start_menu_shortcut| Message::SetInstallOptions(
+                                InstallOptions {
+                                    start_menu_shortcut,
+                                    pin_to_taskbar: start_menu_shortcut && options.pin_to_taskbar,
+                                    ..*options
+                                }
+                            )),
+                        checkbox(options.create_restore_point)
+                            .label(tr(Key::CreateRestorePoint, lang))
+                            .text_size(12)
+                            .on_toggle(|create_restore_point| Message::SetInstallOptions(
+                                InstallOptions {
+                                    create_restore_point,
+                                    ..*options
+                                }
+                            )),
+                        checkbox(options.run_on_startup)
+                            .label(tr(Key::StartWithWindows, lang))
+                            .text_size(12)
+                            .on_toggle(|run_on_startup| Message::SetInstallOptions(
+                                InstallOptions {
+                                    run_on_startup,
+                                    ..*options
+                                }
+                            )),
+                    ]
+                    .spacing(10)),
+                    checkbox(options.portable)
+                        .label(tr(Key::InstallPortable, lang))
+                        .text_size(12)
+                        .on_toggle(|portable| Message::SetInstallOptions(InstallOptions {
+                            portable,
+                            ..*options
+                        })),
                     row![
-                        button(install_str)
+                        button(text(install_str))
                             .style(style::rounded_button)
-                            .on_press(Message::Download(version.clone(), os, arch)),
-                        button("Download Portable")
+                            .on_press_maybe(has_space.then(|| Message::ReviewChanges(
+                                selected_version.clone(),
+                                *os,
+                                *arch,
+                                install_dir.clone(),
+                                *options
+                            ))),
+                        button(tr(Key::DownloadPortable, lang))
                             .style(style::rounded_secondary_button)
-                            .on_press(Message::DownloadPortable(version.clone(), os, arch)),
+                            .on_press(Message::DownloadPortable(
+                                selected_version.clone(),
+                                *os,
+                                *arch
+                            )),
                     ]
                     .spacing(10)
                 ]
@@ -82,86 +1423,807 @@ impl State {
                 .align_x(Alignment::Center)
                 .into()
             }
-            State::Downloading(version) => text(format!("Downloading v{}", version)).into(),
-            State::Installing(version) => text(format!("Installing v{}", version)).into(),
-            State::Installed(version) => column![
-                text(format!("TinyWiiBackupManager v{} installed", version)),
-                button("→ Launch TinyWiiBackupManager")
+            State::Downloading(version, progress, _, previous) => {
+                let downloaded = progress.downloaded;
+                let progress: Element<'_, Message> = match progress.total {
+                    Some(total) if total > 0 => {
+                        let fraction = downloaded as f32 / total as f32;
+                        // A stalled (or not yet measured) download has no meaningful ETA
+                        let remaining = match progress.remaining_secs() {
+                            Some(secs) => trf(Key::Remaining, lang, &[&format_duration(secs)]),
+                            None => "…".to_string(),
+                        };
+
+                        overall_progress(fraction * DOWNLOAD_SHARE, remaining)
+                    }
+                    // No Content-Length, so just spin
+                    _ => text(format!(
+                        "{} {:.1} MiB",
+                        spinner,
+                        downloaded as f32 / (1024.0 * 1024.0)
+                    ))
+                    .into(),
+                };
+
+                let fallback_note = matches!(
+                    **previous,
+                    State::GotLatestVersion {
+                        fell_back_from_v3: true,
+                        ..
+                    }
+                )
+                .then(|| text(tr(Key::V3Fallback, lang)).size(12));
+                let signature_note = (!installer::checks_signature()).then(|| {
+                    text(tr(Key::SignatureNotChecked, lang))
+                        .size(12)
+                        .style(text::danger)
+                });
+
+                column![
+                    text(trf(Key::Downloading, lang, &[version])),
+                    fallback_note,
+                    signature_note,
+                    progress,
+                    button(tr(Key::Cancel, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Cancel),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center)
+                .into()
+            }
+            State::Installing(version, step, ..) => column![
+                text(trf(Key::Installing, lang, &[version])),
+                {
+                    // Each kind of step gets an equal slice of what's left after the download
+                    let (done, status) = match step {
+                        Some(ProgressEvent::Extracting) => (0.0, tr(Key::Extracting, lang).into()),
+                        Some(ProgressEvent::ExtractedFile { done, total }) => (
+                            *done as f32 / (*total).max(1) as f32,
+                            trf(Key::ExtractedFiles, lang, &[done, total]),
+                        ),
+                        Some(ProgressEvent::CreatingShortcuts) => {
+                            (1.0, tr(Key::CreatingShortcuts, lang).into())
+                        }
+                        Some(ProgressEvent::WritingRegistry) => {
+                            (2.0, tr(Key::WritingRegistry, lang).into())
+                        }
+                        _ => (0.0, String::new()),
+                    };
+                    let fraction = DOWNLOAD_SHARE + (1.0 - DOWNLOAD_SHARE) * done / 3.0;
+
+                    overall_progress(fraction, status)
+                },
+                button(tr(Key::Cancel, lang))
+                    .style(style::rounded_secondary_button)
+                    .on_press(Message::Cancel),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::CancellingInstall(_) => {
+                text(trf(Key::CancellingInstall, lang, &[&spinner])).into()
+            }
+            State::Installed(report) if util::is_dry_run() => column![
+                text(trf(Key::DryRunDone, lang, &[&report.version])),
+                text(tr(Key::DryRunSeeLog, lang)).size(12),
+                button(tr(Key::Close, lang))
                     .style(style::rounded_button)
-                    .on_press(Message::LaunchTwbm)
+                    .on_press(Message::Close),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::Installed(report) => column![
+                text(trf(Key::Installed, lang, &[&report.version])),
+                text(trf(
+                    Key::InstalledSummary,
+                    lang,
+                    &[
+                        &report.install_dir.display(),
+                        &format_duration(report.elapsed.as_secs())
+                    ]
+                ))
+                .size(12),
+                column(
+                    report
+                        .actions
+                        .iter()
+                        .filter_map(|action| summary_line(action, lang))
+                        .map(|line| text(format!("• {}", line)).size(12).into())
+                )
+                .spacing(2),
+                column(
+                    report
+                        .warnings
+                        .iter()
+                        .map(|warning| text(warning).size(12).style(text::warning).into())
+                )
+                .spacing(5)
+                .align_x(Alignment::Center),
+                row![
+                    button(tr(Key::Close, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Close),
+                    button(tr(Key::Launch, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Launch),
+                    button(tr(Key::LaunchAndClose, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::LaunchTwbm),
+                ]
+                .spacing(10),
+                button(text(tr(Key::OpenInstallFolder, lang)).size(12))
+                    .style(button::text)
+                    .on_press(Message::OpenInstallFolder),
             ]
             .spacing(10)
             .align_x(Alignment::Center)
             .into(),
             State::InstalledPortable(version, path) => column![
-                text(format!("TinyWiiBackupManager v{} installed", version)),
-                button("→ Launch TinyWiiBackupManager")
+                text(trf(Key::PortableSaved, lang, &[version])),
+                text(path.display().to_string()).size(12),
+                row![
+                    button(tr(Key::Close, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Close),
+                    button(tr(Key::ShowInFolder, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press_maybe(
+                            path.parent()
+                                .map(|dir| Message::ShowInFolder(dir.to_path_buf()))
+                        ),
+                    button(tr(Key::RunPortable, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::LaunchTwbmPortable(path.clone())),
+                ]
+                .spacing(10)
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::UnsupportedOs(is_installed) => column![
+                text(tr(Key::UnsupportedOs, lang)),
+                row![
+                    is_installed.then(|| button(tr(Key::Uninstall, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::AskUninstall)),
+                    button(tr(Key::Close, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::Close),
+                ]
+                .spacing(10)
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::AskingUninstallConfirmation(is_uninstaller) => column![
+                text(tr(Key::AskUninstall, lang)),
+                row![
+                    button(tr(Key::Cancel, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::CancelUninstall(*is_uninstaller)),
+                    button(tr(Key::UninstallKeepData, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Uninstall(true)),
+                    button(tr(Key::UninstallEverything, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::Uninstall(false)),
+                ]
+                .spacing(10)
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::Uninstalling => text(tr(Key::Uninstalling, lang)).into(),
+            State::Uninstalled => column![
+                text(tr(Key::Uninstalled, lang)),
+                button(tr(Key::Close, lang))
                     .style(style::rounded_button)
-                    .on_press(Message::LaunchTwbmPortable(path.clone()))
+                    .on_press(Message::Close),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::NeedsElevation(install_dir, scope, _) => column![
+                text(trf(Key::NeedsAdmin, lang, &[&install_dir.display()])),
+                row![
+                    button(tr(Key::Back, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Cancel),
+                    button(tr(Key::RelaunchAsAdmin, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::RelaunchElevated(install_dir.clone(), *scope)),
+                ]
+                .spacing(10)
             ]
             .spacing(10)
             .align_x(Alignment::Center)
             .into(),
-            State::Errored(msg) => text(format!("Error: {}", msg)).into(),
+            State::ReviewingChanges(changes, download, _) => column![
+                text(tr(
+                    if util::is_dry_run() {
+                        Key::ChangesWouldBeMade
+                    } else {
+                        Key::ChangesWillBeMade
+                    },
+                    lang
+                )),
+                match download.as_ref() {
+                    Message::Download(_, _, arch, ..)
+                        if !arch.is_compatible_with(util::get_arch()) =>
+                    {
+                        Some(
+                            text(trf(Key::ArchIncompatibleReview, lang, &[arch]))
+                                .size(12)
+                                .style(text::danger),
+                        )
+                    }
+                    _ => None,
+                },
+                (matches!(download.as_ref(), Message::Download(..))
+                    && !installer::checks_signature())
+                .then(|| text(tr(Key::SignatureNotChecked, lang))
+                    .size(12)
+                    .style(text::danger)),
+                scrollable(
+                    column(
+                        changes
+                            .iter()
+                            .map(|change| text(format!("• {}", change)).size(12).into())
+                    )
+                    .spacing(5)
+                )
+                .height(Length::Fill),
+                row![
+                    button(tr(Key::Back, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Cancel),
+                    button(tr(Key::Install, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::ConfirmInstall),
+                ]
+                .spacing(10)
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::AppRunning(..) => column![
+                text(tr(Key::AppRunning, lang)),
+                row![
+                    button(tr(Key::Back, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Cancel),
+                    button(tr(Key::CloseItForMe, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::CloseRunningApp),
+                    button(tr(Key::Retry, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::RetryDownload),
+                ]
+                .spacing(10)
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::ConfirmingMetered(..) => column![
+                text(tr(Key::MeteredConnection, lang)),
+                row![
+                    button(tr(Key::Back, lang))
+                        .style(style::rounded_secondary_button)
+                        .on_press(Message::Cancel),
+                    button(tr(Key::Continue, lang))
+                        .style(style::rounded_button)
+                        .on_press(Message::ConfirmMetered),
+                ]
+                .spacing(10)
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            State::Errored {
+                error: e,
+                show_details,
+                resume,
+                ..
+            } => {
+                // Whether it's a warning not to go any further rather than a tip
+                let hint = match e.root() {
+                    InstallError::Permission(_) => Some((Key::HintPermission, false)),
+                    InstallError::Checksum { .. } => Some((Key::HintChecksum, false)),
+                    InstallError::InUse => Some((Key::HintInUse, false)),
+                    InstallError::DownloadTooSmall(_) | InstallError::NotAZip => {
+                        Some((Key::HintBadDownload, false))
+                    }
+                    #[cfg(feature = "signature")]
+                    InstallError::Signature(_) => Some((Key::HintSignature, true)),
+                    _ => None,
+                }
+                .map(|(hint, is_warning)| {
+                    text(tr(hint, lang))
+                        .size(if is_warning { 16 } else { 12 })
+                        .style(if is_warning {
+                            text::danger
+                        } else {
+                            text::default
+                        })
+                });
+
+                // Network and checksum errors are usually transient, as is a captive portal.
+                // Picking up where it failed beats starting over, when there's somewhere to go back to.
+                let transient = matches!(
+                    e.root(),
+                    InstallError::Network(_)
+                        | InstallError::Checksum { .. }
+                        | InstallError::InUse
+                        | InstallError::DownloadTooSmall(_)
+                        | InstallError::NotAZip
+                );
+                let retry = match resume {
+                    Some(_) => Some(
+                        button(tr(Key::TryAgain, lang))
+                            .style(style::rounded_button)
+                            .on_press(Message::TryAgain),
+                    ),
+                    None => transient.then(|| {
+                        button(tr(Key::Retry, lang))
+                            .style(style::rounded_button)
+                            .on_press(Message::Retry)
+                    }),
+                };
+
+                let details = show_details.then(|| {
+                    scrollable(text(log::tail(20).join("\n")).size(11))
+                        .height(120)
+                        .width(Length::Fill)
+                });
+
+                column![
+                    text(trf(Key::Error, lang, &[e])),
+                    hint,
+                    retry,
+                    row![
+                        button(tr(Key::CopyErrorDetails, lang))
+                            .style(style::rounded_secondary_button)
+                            .on_press(Message::CopyErrorReport),
+                        button(tr(Key::ReportProblem, lang))
+                            .style(style::rounded_secondary_button)
+                            .on_press(Message::ReportProblem),
+                        button(tr(Key::Close, lang))
+                            .style(style::rounded_secondary_button)
+                            .on_press(Message::Close),
+                    ]
+                    .spacing(10),
+                    button(
+                        text(format!(
+                            "{} {}",
+                            tr(Key::Details, lang),
+                            if *show_details { "▴" } else { "▾" }
+                        ))
+                        .size(12)
+                    )
+                    .style(button::text)
+                    .on_press(Message::ToggleDetails),
+                    details,
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center)
+                .into()
+            }
         };
 
-        container(content).center(Length::Fill).padding(10).into()
-    }
+        let card = container(content).style(style::card).padding(15);
+
+        container(card).center(Length::Fill).padding(10).into()
+    }
+
+    fn update(&mut self, message: Message, lang: Lang) -> Task<Message> {
+        match message {
+            Message::GotLatestVersion(_, res) if matches!(self, State::Manage { .. }) => {
+                if let Err(e) = &res {
+                    log::write(format!("Could not check for updates: {}", e));
+                }
+                if let State::Manage {
+                    latest_version,
+                    skipped_version,
+                    ..
+                } = self
+                {
+                    // Something newer than the skipped version came out, so it's worth a mention again
+                    if let Ok(latest) = &res
+                        && skipped_version
+                            .as_ref()
+                            .is_some_and(|skipped| util::is_newer(latest, skipped))
+                    {
+                        *skipped_version = None;
+                        util::update_prefs(|prefs| prefs.skipped_version = None);
+                    }
+                    *latest_version = Some(res);
+                }
+
+                Task::none()
+            }
+            Message::VerifyInstallation(version, os, arch) => {
+                let State::Manage {
+                    verifying,
+                    verification,
+                    ..
+                } = self
+                else {
+                    return Task::none();
+                };
+                *verifying = true;
+                *verification = None;
+
+                log::write(format!("Verifying the v{} install", version));
+                run_in_thread(move |mut tx| {
+                    let res = block_on(installer::verify_installation(version, os, arch))
+                        .map_err(|e| e.to_string());
+                    let _ = block_on(tx.send(Message::VerifiedInstallation(res)));
+                })
+            }
+            Message::VerifiedInstallation(res) => {
+                if let Err(e) = &res {
+                    log::write(format!("Could not verify the install: {}", e));
+                }
+                if let State::Manage {
+                    verifying,
+                    verification,
+                    ..
+                } = self
+                {
+                    *verifying = false;
+                    *verification = Some(res);
+                }
+
+                Task::none()
+            }
+            Message::DismissWelcome => {
+                if let State::Welcome {
+                    got_latest_version, ..
+                } = self
+                {
+                    *self = mem::replace(
+                        got_latest_version,
+                        State::FetchingLatestVersion(Channel::default(), None),
+                    );
+                }
+
+                Task::none()
+            }
+            Message::SetSkipWelcome(skip_welcome) => {
+                if let State::Welcome {
+                    skip_welcome: skip, ..
+                } = self
+                {
+                    *skip = skip_welcome;
+                }
+                util::update_prefs(|prefs| prefs.skip_welcome = skip_welcome);
+                Task::none()
+            }
+            Message::SkipVersion(version) => {
+                if let State::Manage {
+                    skipped_version, ..
+                } = self
+                {
+                    log::write(format!("Skipping v{}", version));
+                    util::update_prefs(|prefs| prefs.skipped_version = Some(version.clone()));
+                    *skipped_version = Some(version);
+                }
+
+                Task::none()
+            }
+            Message::ShowInstaller => {
+                let State::Manage {
+                    latest_version,
+                    channel,
+                    ..
+                } = self
+                else {
+                    return Task::none();
+                };
+                let (latest_version, channel) = (latest_version.take(), *channel);
+
+                // A check that's still running lands on the fetching screen instead
+                *self = State::FetchingLatestVersion(channel, None);
+                match latest_version {
+                    Some(Ok(version)) => {
+                        self.update(Message::GotLatestVersion(channel, Ok(version)), lang)
+                    }
+                    Some(Err(_)) => fetch_latest_version(channel),
+                    None => Task::none(),
+                }
+            }
+            Message::GotReleaseNotes(..) | Message::GotVersions(_)
+                if matches!(self, State::Welcome { .. }) =>
+            {
+                let State::Welcome {
+                    got_latest_version, ..
+                } = self
+                else {
+                    return Task::none();
+                };
+                got_latest_version.update(message, lang)
+            }
+            Message::GotLatestVersion(channel, res) => match res {
+                Ok(version) => {
+                    let install_dir = match self {
+                        State::FetchingLatestVersion(_, install_dir) => install_dir.take(),
+                        _ => None,
+                    };
+                    // Only for a fresh start, not after picking a dir or a channel
+                    let welcome = install_dir.is_none() && !util::load_prefs().skip_welcome;
+                    *self = State::got_latest_version(version.clone(), channel, install_dir);
+                    if welcome
+                        && let State::GotLatestVersion {
+                            is_installed: false,
+                            installed_version: None,
+                            ..
+                        } = self
+                    {
+                        *self = State::Welcome {
+                            got_latest_version: self.take_previous(),
+                            skip_welcome: false,
+                        };
+                    }
+                    Task::batch([
+                        fetch_release_notes(version),
+                        Task::perform(
+                            util::list_versions().map_err(|e| e.to_string()),
+                            Message::GotVersions,
+                        ),
+                    ])
+                }
+                Err(e) => {
+                    log::write(format!("Could not fetch the latest version: {}", e));
+                    *self = if util::is_online() {
+                        State::CouldNotFetchLatestVersion(e)
+                    } else {
+                        State::offline()
+                    };
+                    Task::none()
+                }
+            },
+            Message::SelectChannel(channel) => {
+                util::update_prefs(|prefs| prefs.channel = channel);
+                let install_dir = match self {
+                    State::GotLatestVersion { install_dir, .. } => Some(mem::take(install_dir)),
+                    _ => None,
+                };
+                *self = State::FetchingLatestVersion(channel, install_dir);
+                fetch_latest_version(channel)
+            }
+            Message::GotReleaseNotes(for_version, res) => {
+                // Release notes are a nice-to-have, so just leave them out on failure
+                if let Ok(notes) = res
+                    && !notes.is_empty()
+                    && let State::GotLatestVersion {
+                        selected_version,
+                        release_notes,
+                        ..
+                    } = self
+                    && *selected_version == for_version
+                {
+                    *release_notes = Some(notes);
+                }
+
+                Task::none()
+            }
+            Message::GotVersions(res) => {
+                // On failure (e.g. when rate limited) only the latest version is offered
+                if let Ok(new_versions) = res
+                    && let State::GotLatestVersion {
+                        version, versions, ..
+                    } = self
+                {
+                    let mut new_versions = new_versions;
+                    if !new_versions.contains(version) {
+                        new_versions.insert(0, version.clone());
+                    }
+                    *versions = new_versions;
+                }
+
+                Task::none()
+            }
+            Message::SelectVersion(new_version) => {
+                if let State::GotLatestVersion {
+                    selected_version,
+                    release_notes,
+                    ..
+                } = self
+                    && *selected_version != new_version
+                {
+                    *selected_version = new_version.clone();
+                    *release_notes = None;
+                    return fetch_release_notes(new_version);
+                }
+
+                Task::none()
+            }
+            Message::SelectOs(new_os) => {
+                if let State::GotLatestVersion { os, .. } = self {
+                    *os = new_os;
+                }
+
+                Task::none()
+            }
+            Message::SelectArch(new_arch) => {
+                if let State::GotLatestVersion {
+                    arch,
+                    fell_back_from_v3,
+                    ..
+                } = self
+                {
+                    *arch = new_arch;
+                    *fell_back_from_v3 = false;
+                }
+
+                Task::none()
+            }
+            Message::ChangeInstallDir => {
+                let parent = DialogBuilder::file()
+                    .set_title(tr(Key::SelectInstallDir, lang))
+                    .open_single_dir()
+                    .show()
+                    .unwrap_or_default();
+
+                if let Some(parent) = parent
+                    && let State::GotLatestVersion {
+                        install_dir,
+                        install_dir_error,
+                        free_space,
+                        ..
+                    } = self
+                {
+                    match util::install_dir_in(parent) {
+                        Ok(new_install_dir) => {
+                            *install_dir = new_install_dir;
+                            *install_dir_error = None;
+                            *free_space = util::free_space(install_dir).ok();
+                            util::update_prefs(|prefs| {
+                                prefs.install_dir = Some(install_dir.clone())
+                            });
+                        }
+                        Err(e) => *install_dir_error = Some(e.to_string()),
+                    }
+                }
+
+                Task::none()
+            }
+            Message::SetInstallOptions(new_options) => {
+                if let State::GotLatestVersion {
+                    options,
+                    install_dir,
+                    free_space,
+                    ..
+                } = self
+                {
+                    // Follow the scope to its default dir, unless one was picked
+                    if new_options.scope != options.scope
+                        && util::default_install_dir(options.scope)
+                            .is_ok_and(|dir| dir == *install_dir)
+                        && let Ok(new_install_dir) = util::default_install_dir(new_options.scope)
+                    {
+                        *install_dir = new_install_dir;
+                        *free_space = util::free_space(install_dir).ok();
+                    }
+
+                    *options = new_options;
+                    util::update_prefs(|prefs| prefs.options = new_options);
+                }
+
+                Task::none()
+            }
+            Message::Download(version, os, arch, install_dir, options) => {
+                let download =
+                    Message::Download(version.clone(), os, arch, install_dir.clone(), options);
+                if self.blocked_install(&install_dir, options, download) {
+                    return Task::none();
+                }
+
+                if util::is_metered_connection() {
+                    let download = Message::Download(version, os, arch, install_dir, options);
+                    let previous = self.take_previous();
+                    *self = State::ConfirmingMetered(Box::new(download), previous);
+                    return Task::none();
+                }
 
-    fn update(&mut self, message: Message) -> Task<Message> {
-        match message {
-            Message::GotLatestVersion(res) => {
-                match res {
-                    Ok(version) => *self = State::GotLatestVersion(version),
-                    Err(e) => *self = State::Errored(e),
+                self.start_download(version, os, arch, install_dir, options)
+            }
+            Message::Progress(event) => {
+                match (&mut *self, event) {
+                    (
+                        State::Downloading(_, progress, _, _),
+                        ProgressEvent::BytesDownloaded(new_progress),
+                    ) => *progress = new_progress,
+                    (State::Installing(_, step, ..), event) => *step = Some(event),
+                    _ => {}
                 }
 
                 Task::none()
             }
-            Message::Download(version, os, arch) => {
-                *self = State::Downloading(version.clone());
-                Task::perform(
-                    util::download(version, os, arch).map_err(|e| e.to_string()),
-                    Message::Downloaded,
-                )
-            }
-            Message::Downloaded(res) => match res {
-                Ok((version, bytes)) => {
-                    *self = State::Installing(version.clone());
-                    Task::perform(
-                        util::install(version, bytes).map_err(|e| e.to_string()),
-                        Message::Installed,
-                    )
+            Message::Downloaded(res, install_dir, options) => match res {
+                Ok((version, bytes)) => self.start_install(version, bytes, install_dir, options),
+                Err(InstallError::NotFound(name)) if self.is_downloading_v3() => {
+                    // Not every release has a v3 build, the baseline one runs everywhere
+                    log::write(format!("{} not found, falling back to x86_64", name));
+                    let Some(version) = self.target_version() else {
+                        return Task::none();
+                    };
+                    let mut previous = self.take_previous();
+                    let (os, arch) = match &mut *previous {
+                        State::GotLatestVersion {
+                            os,
+                            arch,
+                            fell_back_from_v3,
+                            ..
+                        } => {
+                            *fell_back_from_v3 = true;
+                            *arch = Arch::X86_64;
+                            (*os, *arch)
+                        }
+                        State::Manage { os, arch, .. } => {
+                            *arch = Arch::X86_64;
+                            (*os, *arch)
+                        }
+                        _ => return Task::none(),
+                    };
+                    *self = *previous;
+                    // Already checked and confirmed for the v3 download
+                    self.start_download(version, os, arch, install_dir, options)
                 }
                 Err(e) => {
-                    *self = State::Errored(e);
+                    self.fail(e);
                     Task::none()
                 }
             },
-            Message::Installed(res) => match res {
-                Ok(version) => {
-                    *self = State::Installed(version);
-                    Task::none()
-                }
-                Err(e) => {
-                    *self = State::Errored(e);
-                    Task::none()
+            Message::Installed(res) => {
+                let cancelled = matches!(self, State::CancellingInstall(_));
+                match res {
+                    // Too late to cancel, the last step was already done
+                    Ok(report) => *self = State::Installed(report),
+                    Err(InstallError::Cancelled) if cancelled => *self = *self.take_previous(),
+                    Err(InstallError::RolledBack {
+                        error, not_undone, ..
+                    }) if cancelled
+                        && matches!(*error, InstallError::Cancelled)
+                        && not_undone.is_empty() =>
+                    {
+                        *self = *self.take_previous()
+                    }
+                    Err(e) => self.fail(e),
                 }
-            },
+
+                Task::none()
+            }
             Message::DownloadPortable(version, os, arch) => {
                 let dest_dir = DialogBuilder::file()
-                    .set_title("Select destination directory")
+                    .set_title(tr(Key::SelectDestinationDir, lang))
                     .open_single_dir()
                     .show()
                     .unwrap_or_default();
 
                 if let Some(dest_dir) = dest_dir {
-                    *self = State::Downloading(version.clone());
-                    Task::perform(
-                        util::download_to_dir(version, os, arch, dest_dir)
-                            .map_err(|e| e.to_string()),
-                        Message::DownloadedPortable,
-                    )
+                    let (task, handle) = run_in_thread({
+                        let version = version.clone();
+                        move |mut tx| {
+                            // Checked like an install's download, so a bad one is never saved
+                            let res = block_on(installer::download(version, os, arch, |event| {
+                                report_progress(&mut tx, event)
+                            }))
+                            .and_then(|(version, bytes)| {
+                                let path = util::save_portable_exe(&version, bytes, &dest_dir)?;
+                                Ok((version, path))
+                            });
+
+                            let _ = block_on(tx.send(Message::DownloadedPortable(res)));
+                        }
+                    })
+                    .abortable();
+
+                    let previous = self.take_previous();
+                    *self = State::Downloading(version, Progress::default(), handle, previous);
+                    task
                 } else {
                     Task::none()
                 }
@@ -172,33 +2234,862 @@ impl State {
                         *self = State::InstalledPortable(version.clone(), path);
                     }
                     Err(e) => {
-                        *self = State::Errored(e);
+                        self.fail(e);
                     }
                 }
                 Task::none()
             }
+            Message::Launch => Task::perform(
+                async { util::launch_twbm().map_err(InstallError::from) },
+                Message::Launched,
+            ),
+            Message::Launched(res) => {
+                if let Err(e) = res {
+                    self.fail(e);
+                }
+
+                Task::none()
+            }
             Message::LaunchTwbm => match util::launch_twbm() {
                 Ok(()) => iced::exit(),
                 Err(e) => {
-                    *self = State::Errored(e.to_string());
+                    self.fail(e.into());
                     Task::none()
                 }
             },
             Message::LaunchTwbmPortable(path) => match util::launch_twbm_portable(path) {
                 Ok(()) => iced::exit(),
                 Err(e) => {
-                    *self = State::Errored(e.to_string());
+                    self.fail(e.into());
                     Task::none()
                 }
             },
+            Message::OpenInstallFolder => {
+                let res = util::install_dir().and_then(|dir| util::open_folder(&dir));
+                if let Err(e) = res {
+                    self.fail(e.into());
+                }
+
+                Task::none()
+            }
+            Message::ShowInFolder(dir) => {
+                if let Err(e) = util::open_folder(&dir) {
+                    self.fail(e.into());
+                }
+
+                Task::none()
+            }
+            Message::InstallFromFile => {
+                let path = DialogBuilder::file()
+                    .set_title(tr(Key::SelectZip, lang))
+                    .add_filter(tr(Key::ZipArchive, lang), ["zip"])
+                    .open_single_file()
+                    .show()
+                    .unwrap_or_default();
+
+                let Some(path) = path else {
+                    return Task::none();
+                };
+
+                // Goes through the same review as a download, before anything is read
+                let res = util::version_from_zip_name(&path).and_then(|_| {
+                    let (install_dir, options) = self.install_target()?;
+                    let changes = util::plan_install(&install_dir, options)?;
+                    Ok((changes, Message::InstallFile(path, install_dir, options)))
+                });
+
+                match res {
+                    Ok((changes, install)) => {
+                        let previous = self.take_previous();
+                        *self = State::ReviewingChanges(changes, Box::new(install), previous);
+                    }
+                    Err(e) => self.fail(e.into()),
+                }
+
+                Task::none()
+            }
+            Message::InstallFile(path, install_dir, options) => {
+                let install = Message::InstallFile(path.clone(), install_dir.clone(), options);
+                if self.blocked_install(&install_dir, options, install) {
+                    return Task::none();
+                }
+
+                match installer::read_release_file(&path) {
+                    Ok((version, bytes)) => {
+                        self.start_install(version, bytes, install_dir, options)
+                    }
+                    Err(e) => {
+                        self.fail(e.into());
+                        Task::none()
+                    }
+                }
+            }
+            Message::ToggleAdvanced => {
+                if let State::GotLatestVersion { show_advanced, .. } = self {
+                    *show_advanced = !*show_advanced;
+                }
+
+                Task::none()
+            }
+            Message::CopyDownloadUrl(url) => iced::clipboard::write(url),
+            Message::ClearCache => {
+                if let Err(e) = util::clear_cache() {
+                    log::write(format!("Failed to clear the download cache: {}", e));
+                }
+
+                if let State::GotLatestVersion { has_cache, .. } = self {
+                    *has_cache = util::has_cache();
+                }
+
+                Task::none()
+            }
+            Message::FixShortcuts => {
+                let res = util::install_dir()
+                    .map_err(InstallError::from)
+                    .and_then(|dir| {
+                        util::create_shortcuts(&dir.join("TinyWiiBackupManager.exe"), &dir)
+                    });
+
+                match res {
+                    Ok(()) => match self {
+                        State::GotLatestVersion {
+                            shortcuts_broken, ..
+                        } => *shortcuts_broken = false,
+                        State::Manage { status, .. } => {
+                            status.shortcuts_valid = true;
+                            status.shortcuts_exist = true;
+                        }
+                        _ => {}
+                    },
+                    Err(e) => self.fail(e),
+                }
+
+                Task::none()
+            }
+            Message::ToggleNetworkSettings => {
+                if let State::GotLatestVersion {
+                    show_network_settings,
+                    ..
+                } = self
+                {
+                    *show_network_settings = !*show_network_settings;
+                }
+
+                Task::none()
+            }
+            Message::ProxyChanged(proxy) => {
+                util::set_proxy_override(Some(proxy));
+                Task::none()
+            }
+            Message::MaxRateChanged(rate) => {
+                // Anything that isn't a number is ignored, an empty field means unlimited
+                if rate.trim().is_empty() {
+                    util::set_max_rate(None);
+                } else if let Ok(rate) = util::parse_max_rate(&rate) {
+                    util::set_max_rate(Some(rate));
+                }
+
+                Task::none()
+            }
+            Message::TryAgain => {
+                let State::Errored { resume, .. } = self else {
+                    return Task::none();
+                };
+
+                match resume.take() {
+                    Some(Resume::Screen(previous)) => {
+                        *self = *previous;
+                        Task::none()
+                    }
+                    Some(Resume::Install(inputs, previous)) => {
+                        log::write(format!("Trying the install of v{} again", inputs.version));
+                        *self = *previous;
+                        let InstallInputs {
+                            version,
+                            bytes,
+                            install_dir,
+                            options,
+                        } = *inputs;
+                        self.start_install(version, bytes, install_dir, options)
+                    }
+                    None => Task::none(),
+                }
+            }
+            Message::Retry => {
+                let channel = Channel::default();
+                *self = State::FetchingLatestVersion(channel, None);
+                fetch_latest_version(channel)
+            }
+            Message::Repair(version) => {
+                let (os, arch) = match self {
+                    State::GotLatestVersion { os, arch, .. } | State::Manage { os, arch, .. } => {
+                        (*os, *arch)
+                    }
+                    State::Offline { .. } => (util::get_os().unwrap_or_default(), util::get_arch()),
+                    _ => return Task::none(),
+                };
+
+                // Same flow as an install, just into the existing dir with its options
+                match util::install_dir() {
+                    Ok(install_dir) => {
+                        log::write(format!("Repairing v{}", version));
+                        let scope = util::installed_scope().unwrap_or_default();
+                        let options = util::installed_options(&install_dir, scope);
+                        self.update(
+                            Message::Download(version, os, arch, install_dir, options),
+                            lang,
+                        )
+                    }
+                    Err(e) => {
+                        self.fail(e.into());
+                        Task::none()
+                    }
+                }
+            }
+            Message::ReviewChanges(version, os, arch, install_dir, options) => {
+                let changes = match util::plan_install(&install_dir, options) {
+                    Ok(changes) => changes,
+                    Err(e) => {
+                        self.fail(e.into());
+                        return Task::none();
+                    }
+                };
+                let download = Message::Download(version, os, arch, install_dir, options);
+                let previous = self.take_previous();
+                *self = State::ReviewingChanges(changes, Box::new(download), previous);
+                Task::none()
+            }
+            Message::ConfirmInstall => {
+                match mem::replace(self, State::FetchingLatestVersion(Channel::default(), None)) {
+                    State::ReviewingChanges(_, download, previous) => {
+                        *self = *previous;
+                        self.update(*download, lang)
+                    }
+                    state => {
+                        *self = state;
+                        Task::none()
+                    }
+                }
+            }
+            Message::CloseRunningApp => {
+                if let Err(e) = util::close_running() {
+                    self.fail(e);
+                    return Task::none();
+                }
+
+                self.update(Message::RetryDownload, lang)
+            }
+            Message::RetryDownload => {
+                match mem::replace(self, State::FetchingLatestVersion(Channel::default(), None)) {
+                    State::AppRunning(download, previous) => {
+                        *self = *previous;
+                        self.update(*download, lang)
+                    }
+                    state => {
+                        *self = state;
+                        Task::none()
+                    }
+                }
+            }
+            Message::ConfirmMetered => {
+                match mem::replace(self, State::FetchingLatestVersion(Channel::default(), None)) {
+                    State::ConfirmingMetered(download, previous) => {
+                        *self = *previous;
+                        match *download {
+                            Message::Download(version, os, arch, install_dir, options) => {
+                                self.start_download(version, os, arch, install_dir, options)
+                            }
+                            download => self.update(download, lang),
+                        }
+                    }
+                    state => {
+                        *self = state;
+                        Task::none()
+                    }
+                }
+            }
+            Message::AskUninstall => {
+                *self = State::AskingUninstallConfirmation(false);
+                Task::none()
+            }
+            Message::Uninstall(keep_user_data) => {
+                *self = State::Uninstalling;
+                run_in_thread(move |mut tx| {
+                    let res = installer::uninstall(keep_user_data);
+                    let _ = block_on(tx.send(Message::Uninstalled(res)));
+                })
+            }
+            Message::Uninstalled(res) => {
+                match res {
+                    Ok(()) => *self = State::Uninstalled,
+                    Err(e) => self.fail(e),
+                }
+                Task::none()
+            }
+            Message::CancelUninstall(is_uninstaller) => {
+                if is_uninstaller {
+                    return iced::exit();
+                }
+
+                let (state, task) = State::new(None);
+                *self = state;
+                task
+            }
+            Message::Close => iced::exit(),
+            Message::ConfirmExit => {
+                log::write("Closed while busy, the install may be incomplete");
+                iced::exit()
+            }
+            Message::ToggleCustomVersion => {
+                if let State::GotLatestVersion {
+                    custom_version,
+                    custom_version_error,
+                    ..
+                } = self
+                {
+                    *custom_version = match custom_version {
+                        Some(_) => None,
+                        None => Some(String::new()),
+                    };
+                    *custom_version_error = None;
+                }
+
+                Task::none()
+            }
+            Message::CustomVersionChanged(input) => {
+                if let State::GotLatestVersion { custom_version, .. } = self {
+                    *custom_version = Some(input);
+                }
+
+                Task::none()
+            }
+            Message::InstallCustomVersion => {
+                let State::GotLatestVersion {
+                    os,
+                    arch,
+                    custom_version: Some(input),
+                    custom_version_error,
+                    ..
+                } = self
+                else {
+                    return Task::none();
+                };
+
+                let version = match util::parse_version_input(input) {
+                    Ok(version) => version,
+                    Err(e) => {
+                        *custom_version_error = Some(e.to_string());
+                        return Task::none();
+                    }
+                };
+
+                *custom_version_error = None;
+                let (os, arch) = (*os, *arch);
+                run_in_thread(move |mut tx| {
+                    let res =
+                        block_on(util::check_asset_exists(&version, os, arch)).map(|()| version);
+                    let _ = block_on(tx.send(Message::CheckedCustomVersion(res)));
+                })
+            }
+            Message::CheckedCustomVersion(res) => {
+                let State::GotLatestVersion {
+                    os,
+                    arch,
+                    install_dir,
+                    custom_version_error,
+                    options,
+                    ..
+                } = self
+                else {
+                    return Task::none();
+                };
+
+                match res {
+                    Ok(version) => {
+                        let message = Message::ReviewChanges(
+                            version,
+                            *os,
+                            *arch,
+                            install_dir.clone(),
+                            *options,
+                        );
+                        self.update(message, lang)
+                    }
+                    Err(e) => {
+                        *custom_version_error = Some(e.to_string());
+                        Task::none()
+                    }
+                }
+            }
+            Message::CopyErrorReport => match self {
+                State::Errored { error, version, .. } => {
+                    iced::clipboard::write(error_report(error, version.as_deref()))
+                }
+                _ => Task::none(),
+            },
+            Message::ReportProblem => {
+                let (title, report) = match self {
+                    State::Errored { error, version, .. } => (
+                        format!("Installer error: {}", error.root()),
+                        error_report(error, version.as_deref()),
+                    ),
+                    State::Manage { latest_version, .. } => (
+                        String::new(),
+                        system_report(
+                            latest_version
+                                .as_ref()
+                                .and_then(|latest| latest.as_ref().ok())
+                                .map(String::as_str),
+                        ),
+                    ),
+                    _ => return Task::none(),
+                };
+
+                let body = format!("What happened:\n\n\n{}", report);
+                log::write("Opening a new issue in the browser");
+                match util::open_url(&util::new_issue_url(&title, &body)) {
+                    Ok(()) => Task::none(),
+                    // The report can still be pasted by hand
+                    Err(e) => {
+                        log::write(format!("Could not open the browser: {}", e));
+                        iced::clipboard::write(report)
+                    }
+                }
+            }
+            Message::ToggleDetails => {
+                if let State::Errored { show_details, .. } = self {
+                    *show_details = !*show_details;
+                }
+
+                Task::none()
+            }
+            Message::Confirm => match self.default_action() {
+                Some(message) => self.update(message, lang),
+                None => Task::none(),
+            },
+            Message::Dismiss => match self.dismiss_action() {
+                Some(message) => self.update(message, lang),
+                None => Task::none(),
+            },
+            // Handled by App
+            Message::SelectTheme(_)
+            | Message::SelectLang(_)
+            | Message::WindowMoved(_)
+            | Message::WindowResized(_)
+            | Message::CloseRequested
+            | Message::Tick => Task::none(),
+            Message::RelaunchElevated(install_dir, scope) => {
+                match util::relaunch_elevated(&install_dir, scope) {
+                    Ok(()) => iced::exit(),
+                    Err(e) => {
+                        self.fail(e.into());
+                        Task::none()
+                    }
+                }
+            }
+            Message::Cancel => {
+                if let State::Downloading(_, _, handle, _) = self {
+                    handle.abort();
+                    *self = *self.take_previous();
+                } else if let State::Installing(_, _, cancel, ..) = self {
+                    // Stays put until the thread is done rolling back
+                    cancel.store(true, Ordering::Relaxed);
+                    let previous = self.take_previous();
+                    *self = State::CancellingInstall(previous);
+                } else if let State::NeedsElevation(..)
+                | State::ReviewingChanges(..)
+                | State::AppRunning(..)
+                | State::ConfirmingMetered(..) = self
+                {
+                    *self = *self.take_previous();
+                }
+
+                Task::none()
+            }
+        }
+    }
+}
+
+/// A Markdown block ready to be pasted into a GitHub issue
+fn error_report(error: &InstallError, version: Option<&str>) -> String {
+    let mut report = format!(
+        "```\n{}Error: {}\nVersion: {}\n",
+        system_info(),
+        error,
+        version.map_or("unknown".to_string(), |version| format!("v{}", version)),
+    );
+
+    if let InstallError::Network(_) = error
+        && let Some(url) = util::last_url()
+    {
+        report.push_str(&format!("URL: {}\n", url));
+    }
+
+    report.push_str("```");
+    report
+}
+
+/// What a report from the manage screen says, with no error to go with it
+fn system_report(latest: Option<&str>) -> String {
+    format!(
+        "```\n{}Latest: {}\n```",
+        system_info(),
+        latest.map_or("unknown".to_string(), |latest| format!("v{}", latest)),
+    )
+}
+
+/// The installer's version, the OS and arch and the installed version, one per line
+fn system_info() -> String {
+    let os = match util::get_build_number() {
+        Ok(build_number) => format!(
+            "{} (build {})",
+            util::get_os().unwrap_or_default(),
+            build_number
+        ),
+        Err(_) => util::get_os().unwrap_or_default().to_string(),
+    };
+
+    let installed = match util::installed_version() {
+        Ok(Some(installed)) => format!("v{}", installed),
+        Ok(None) => "none".to_string(),
+        Err(_) => "unknown".to_string(),
+    };
+
+    format!(
+        "TinyWiiBackupManagerInstaller v{}\nOS: {}\nArch: {}\nInstalled: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        os,
+        util::get_arch(),
+        installed,
+    )
+}
+
+/// The fetched latest version, if it's newer than the installed one (or that's unknown)
+fn available_update<'a>(
+    status: &installer::Status,
+    latest_version: &'a Option<Result<String, String>>,
+) -> Option<&'a String> {
+    let Some(Ok(latest)) = latest_version else {
+        return None;
+    };
+
+    status
+        .installed_version
+        .as_ref()
+        .is_none_or(|installed| util::is_newer(latest, installed))
+        .then_some(latest)
+}
+
+/// CI builds are labelled as such, their versions aren't release numbers
+fn installed_version_text(installed: &str, lang: Lang) -> String {
+    if util::is_nightly(installed) {
+        trf(Key::InstalledNightly, lang, &[&installed])
+    } else {
+        trf(Key::InstalledVersion, lang, &[&installed])
+    }
+}
+
+/// How much of the bar the download takes up, the install gets the rest
+const DOWNLOAD_SHARE: f32 = 0.8;
+
+/// The bar shared by the download and install screens, so going from one to the other doesn't jump
+fn overall_progress<'a>(fraction: f32, status: String) -> Element<'a, Message> {
+    column![
+        progress_bar(0.0..=1.0, fraction).girth(10),
+        text(format!("{:.0}%", fraction * 100.0)),
+        text(status).size(12),
+    ]
+    .spacing(5)
+    .align_x(Alignment::Center)
+    .into()
+}
+
+fn format_size(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+
+    match bytes as f64 / MIB {
+        mib if mib >= 1024.0 => format!("{:.1} GiB", mib / 1024.0),
+        mib => format!("{:.0} MiB", mib),
+    }
+}
+
+/// Like "12s" or "3m 05s"
+fn format_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        _ => format!("{}m {:02}s", secs / 60, secs % 60),
+    }
+}
+
+/// How the Installed screen lists the optional steps that ran, the others always do
+fn summary_line(action: &InstallAction, lang: Lang) -> Option<&'static str> {
+    let key = match action {
+        InstallAction::CreateRestorePoint => Key::SummaryRestorePoint,
+        InstallAction::CreateDesktopShortcut(_) => Key::SummaryDesktopShortcut,
+        InstallAction::CreateStartMenuShortcut(_) => Key::SummaryStartMenuShortcut,
+        InstallAction::PinToTaskbar(_) => Key::SummaryPinToTaskbar,
+        InstallAction::AddToPath(..) => Key::SummaryAddToPath,
+        InstallAction::RegisterFileAssociations {
+            include_iso: true, ..
+        } => Key::SummaryFileAssociationsIso,
+        InstallAction::RegisterFileAssociations { .. } => Key::SummaryFileAssociations,
+        InstallAction::RunOnStartup => Key::SummaryRunOnStartup,
+        InstallAction::RemoveManagedFiles(_)
+        | InstallAction::ExtractFiles(_)
+        | InstallAction::CopyUninstaller(_)
+        | InstallAction::WriteUninstallKeys(_)
+        | InstallAction::WritePortableMarker(_) => return None,
+    };
+
+    Some(tr(key, lang))
+}
+
+/// Fetch → Download → Install → Done, highlighting up to the current stage
+fn stepper<'a>(current: usize, lang: Lang) -> Element<'a, Message> {
+    let mut steps = row![].spacing(6).align_y(Alignment::Center);
+
+    for (i, label) in STAGES.into_iter().map(|stage| tr(stage, lang)).enumerate() {
+        if i > 0 {
+            steps = steps.push(container(rule::horizontal(1)).width(20));
         }
+
+        let circle = container(text(i + 1).size(12))
+            .center(22)
+            .style(style::step(i <= current));
+        let label = if i == current {
+            text(label).size(12).style(text::primary)
+        } else {
+            text(label).size(12)
+        };
+
+        steps = steps.push(row![circle, label].spacing(4).align_y(Alignment::Center));
+    }
+
+    steps.into()
+}
+
+/// Lets the user override the proxy picked up from HTTPS_PROXY/HTTP_PROXY,
+/// and cap the download speed
+fn network_settings<'a>(lang: Lang) -> Element<'a, Message> {
+    let proxy = util::proxy_override().unwrap_or_default();
+    let max_rate = util::max_rate()
+        .map(|rate| rate.to_string())
+        .unwrap_or_default();
+
+    column![
+        row![
+            text(tr(Key::Proxy, lang)).size(12),
+            text_input(tr(Key::ProxyPlaceholder, lang), &proxy)
+                .on_input(Message::ProxyChanged)
+                .size(12),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text(tr(Key::MaxRate, lang)).size(12),
+            text_input(tr(Key::MaxRatePlaceholder, lang), &max_rate)
+                .on_input(Message::MaxRateChanged)
+                .size(12)
+                .width(120),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(6)
+    .into()
+}
+
+fn fetch_release_notes(version: String) -> Task<Message> {
+    Task::perform(
+        util::get_release_notes(version.clone()).map_err(|e| e.to_string()),
+        move |res| Message::GotReleaseNotes(version.clone(), res),
+    )
+}
+
+fn fetch_latest_version(channel: Channel) -> Task<Message> {
+    Task::perform(
+        installer::fetch_latest(channel).map_err(|e| e.to_string()),
+        move |res| Message::GotLatestVersion(channel, res),
+    )
+}
+
+/// Forwards download and install progress, telling them to stop once nobody is listening
+fn report_progress(tx: &mut mpsc::Sender<Message>, event: ProgressEvent) -> ControlFlow<()> {
+    match tx.try_send(Message::Progress(event)) {
+        Err(e) if e.is_disconnected() => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
     }
 }
 
-fn main() -> iced::Result {
-    iced::application(State::new, State::update, State::view)
-        .window_size(Size::new(500.0, 300.0))
-        .resizable(false)
-        .title("Install TinyWiiBackupManager")
-        .run()
+/// A Message::Tick every TICK, stopping once the subscription is dropped.
+/// The thread-pool executor has no timers, so a thread does the sleeping.
+fn ticks() -> impl Stream<Item = Message> {
+    let (mut tx, rx) = mpsc::channel(1);
+    thread::spawn(move || {
+        loop {
+            thread::sleep(TICK);
+            if block_on(tx.send(Message::Tick)).is_err() {
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+/// Saves panics to installer-crash.log, and points the user at it since a release build
+/// has no console to print them to
+fn install_panic_hook(headless: bool) {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        let report = format!(
+            "Installer v{} panicked: {}\n\n{}",
+            env!("CARGO_PKG_VERSION"),
+            info,
+            Backtrace::force_capture()
+        );
+        log::write(format!("Panicked: {}", info));
+        let path = log::write_crash_report(&report);
+
+        // The command line is English only, the GUI is in the language picked last
+        let lang = if headless {
+            Lang::English
+        } else {
+            util::load_prefs().lang.unwrap_or_else(Lang::detect)
+        };
+        let text = match &path {
+            Some(path) => trf(Key::CrashedReportSaved, lang, &[&path.display()]),
+            None => tr(Key::Crashed, lang).to_string(),
+        };
+
+        if headless {
+            eprintln!("{}", text);
+        } else {
+            let _ = DialogBuilder::message()
+                .set_level(MessageLevel::Error)
+                .set_title(tr(Key::DialogTitle, lang))
+                .set_text(text)
+                .alert()
+                .show();
+        }
+    }));
+}
+
+/// Enter and Esc, unless a widget (like the version input) already used them
+fn keyboard_shortcuts() -> Subscription<Message> {
+    keyboard::listen().filter_map(|event| match event {
+        keyboard::Event::KeyPressed {
+            key: keyboard::Key::Named(key::Named::Enter),
+            ..
+        } => Some(Message::Confirm),
+        keyboard::Event::KeyPressed {
+            key: keyboard::Key::Named(key::Named::Escape),
+            ..
+        } => Some(Message::Dismiss),
+        _ => None,
+    })
+}
+
+/// So the window opens where (and as big as) it was left, and closing can be confirmed
+fn window_changes() -> Subscription<Message> {
+    window::events().filter_map(|(_, event)| match event {
+        window::Event::Moved(position) => Some(Message::WindowMoved(position)),
+        window::Event::Resized(size) => Some(Message::WindowResized(size)),
+        window::Event::CloseRequested => Some(Message::CloseRequested),
+        _ => None,
+    })
+}
+
+/// Runs a blocking job on its own thread so it doesn't stall the executor,
+/// producing every message the job sends back.
+fn run_in_thread(job: impl FnOnce(mpsc::Sender<Message>) + Send + 'static) -> Task<Message> {
+    let (tx, rx) = mpsc::channel(100);
+    thread::spawn(move || job(tx));
+    Task::stream(rx)
+}
+
+/// The logo as the window icon, or the default one if it doesn't decode
+fn window_icon() -> Option<window::Icon> {
+    let logo = ::image::load_from_memory_with_format(LOGO, ::image::ImageFormat::Png)
+        .ok()?
+        .into_rgba8();
+    let (width, height) = logo.dimensions();
+
+    window::icon::from_rgba(logo.into_raw(), width, height).ok()
+}
+
+fn main() -> ExitCode {
+    let args = match cli::Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            cli::attach_console();
+            return cli::print_usage_error(&e);
+        }
+    };
+
+    install_panic_hook(args.is_headless());
+    util::set_dry_run(args.dry_run);
+    util::set_base_url_override(args.base_url.clone());
+    util::set_mirrors(args.mirrors.clone());
+    util::set_max_rate(args.max_rate);
+
+    // Passed along by an elevated relaunch, the GUI then starts with it preselected
+    if args.all_users && !args.is_headless() {
+        util::update_prefs(|prefs| prefs.options.scope = Scope::AllUsers);
+    }
+
+    if args.is_headless() {
+        cli::attach_console();
+        return cli::run(args);
+    }
+
+    // A bad --install-dir (from an elevated relaunch) just means picking it again
+    let install_dir = args
+        .install_dir
+        .and_then(|dir| match util::install_dir_in(dir) {
+            Ok(dir) => Some(dir),
+            Err(e) => {
+                log::write(format!("Ignoring --install-dir: {}", e));
+                None
+            }
+        });
+    let prefs = util::load_prefs();
+    let position = match prefs.window_position {
+        Some((x, y)) if util::is_on_screen(x, y) => {
+            window::Position::Specific(Point::new(x as f32, y as f32))
+        }
+        _ => window::Position::Centered,
+    };
+    let size = prefs
+        .window_size
+        .map(|(width, height)| Size::new(width as f32, height as f32))
+        .filter(|size| size.width >= MIN_WINDOW_SIZE.width && size.height >= MIN_WINDOW_SIZE.height)
+        .unwrap_or(WINDOW_SIZE);
+
+    let res = iced::application(
+        move || App::new(install_dir.clone()),
+        App::update,
+        App::view,
+    )
+    .theme(App::theme)
+    .subscription(App::subscription)
+    .window(window::Settings {
+        size,
+        position,
+        min_size: Some(MIN_WINDOW_SIZE),
+        icon: window_icon(),
+        // Message::CloseRequested decides, so a running install isn't cut off unasked
+        exit_on_close_request: false,
+        ..Default::default()
+    })
+    .title(App::title)
+    .run();
+
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::FAILURE,
+    }
 }
@@ -1,18 +1,165 @@
 // SPDX-FileCopyrightText: 2026 Manuel Quarneti <mq1@ik.me>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use iced::{Theme, border, widget::button};
+use iced::{
+    Color, Theme, border,
+    theme::Palette,
+    widget::{button, container, pick_list},
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set along with the theme, so the styles below can switch to their high-contrast variant
+static HIGH_CONTRAST: AtomicBool = AtomicBool::new(false);
+
+/// How thick borders get in high contrast
+const HIGH_CONTRAST_BORDER: f32 = 2.0;
+
+pub fn set_high_contrast(enabled: bool) {
+    HIGH_CONTRAST.store(enabled, Ordering::Relaxed);
+}
+
+fn is_high_contrast() -> bool {
+    HIGH_CONTRAST.load(Ordering::Relaxed)
+}
+
+/// White on black with a yellow accent, like the Windows high-contrast themes
+pub fn high_contrast_theme() -> Theme {
+    Theme::custom(
+        "High contrast",
+        Palette {
+            background: Color::BLACK,
+            text: Color::WHITE,
+            primary: Color::from_rgb8(0xFF, 0xD7, 0x00),
+            success: Color::from_rgb8(0x3F, 0xF2, 0x3F),
+            warning: Color::from_rgb8(0xFF, 0xA5, 0x00),
+            danger: Color::from_rgb8(0xFF, 0x6B, 0x6B),
+        },
+    )
+}
 
 pub fn rounded_button(theme: &Theme, status: button::Status) -> button::Style {
+    if is_high_contrast() {
+        return rounded_button_high_contrast(theme, status);
+    }
+
     let mut style = button::primary(theme, status);
     style.border.radius = border::radius(30);
     style
 }
 
+pub fn rounded_button_high_contrast(theme: &Theme, status: button::Status) -> button::Style {
+    let mut style = button::primary(theme, status);
+    style.border = border::rounded(30)
+        .width(HIGH_CONTRAST_BORDER)
+        .color(theme.palette().text);
+    style
+}
+
 pub fn rounded_secondary_button(theme: &Theme, status: button::Status) -> button::Style {
+    if is_high_contrast() {
+        return rounded_secondary_button_high_contrast(theme, status);
+    }
+
     let mut style = button::background(theme, status);
     style.border.width = 1.0;
     style.border.color = theme.extended_palette().background.strong.color;
     style.border.radius = border::radius(30);
     style
 }
+
+/// Outlined in the accent color when hovered, so the focus is easy to follow
+pub fn rounded_secondary_button_high_contrast(
+    theme: &Theme,
+    status: button::Status,
+) -> button::Style {
+    let palette = theme.palette();
+    let mut style = button::background(theme, status);
+    style.text_color = palette.text;
+    style.border = border::rounded(30)
+        .width(HIGH_CONTRAST_BORDER)
+        .color(match status {
+            button::Status::Hovered | button::Status::Pressed => palette.primary,
+            button::Status::Active | button::Status::Disabled => palette.text,
+        });
+    style
+}
+
+pub fn card(theme: &Theme) -> container::Style {
+    if is_high_contrast() {
+        return card_high_contrast(theme);
+    }
+
+    let palette = theme.extended_palette();
+
+    container::Style::default()
+        .background(palette.background.weak.color)
+        .border(
+            border::rounded(15)
+                .width(1)
+                .color(palette.background.strong.color),
+        )
+}
+
+pub fn card_high_contrast(theme: &Theme) -> container::Style {
+    let palette = theme.palette();
+
+    container::Style::default()
+        .background(palette.background)
+        .color(palette.text)
+        .border(
+            border::rounded(15)
+                .width(HIGH_CONTRAST_BORDER)
+                .color(palette.text),
+        )
+}
+
+/// A stepper circle, filled in once its stage is reached
+pub fn step(reached: bool) -> impl Fn(&Theme) -> container::Style {
+    move |theme| {
+        let palette = theme.extended_palette();
+        let pair = if reached {
+            palette.primary.base
+        } else {
+            palette.background.strong
+        };
+
+        let border = if is_high_contrast() {
+            border::rounded(11)
+                .width(HIGH_CONTRAST_BORDER)
+                .color(theme.palette().text)
+        } else {
+            border::rounded(11)
+        };
+
+        container::Style::default()
+            .background(pair.color)
+            .color(pair.text)
+            .border(border)
+    }
+}
+
+pub fn rounded_pick_list(theme: &Theme, status: pick_list::Status) -> pick_list::Style {
+    if is_high_contrast() {
+        return rounded_pick_list_high_contrast(theme, status);
+    }
+
+    let mut style = pick_list::default(theme, status);
+    style.border.radius = border::radius(30);
+    style
+}
+
+pub fn rounded_pick_list_high_contrast(
+    theme: &Theme,
+    status: pick_list::Status,
+) -> pick_list::Style {
+    let palette = theme.palette();
+    let mut style = pick_list::default(theme, status);
+    style.text_color = palette.text;
+    style.border = border::rounded(30)
+        .width(HIGH_CONTRAST_BORDER)
+        .color(match status {
+            pick_list::Status::Hovered | pick_list::Status::Opened { .. } => palette.primary,
+            pick_list::Status::Active => palette.text,
+        });
+    style
+}
@@ -1,257 +1,5697 @@
 // SPDX-FileCopyrightText: 2026 Manuel Quarneti <mq1@ik.me>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use anyhow::{Result, anyhow};
+use crate::i18n::Lang;
+use crate::log;
+use anyhow::{Result, anyhow, bail};
 use directories::{BaseDirs, UserDirs};
-use mslnk::ShellLink;
-use std::fs::File;
-use std::io;
-use std::os::windows::process::CommandExt;
-use std::path::PathBuf;
-use std::{env, fs, io::Cursor, process::Command};
-use windows_registry::{CURRENT_USER, LOCAL_MACHINE};
-use zip::ZipArchive;
-
-const UNINSTALL_PS1: &[u8] = include_bytes!("../uninstall.ps1");
-
-pub async fn install(version: String, bytes: Vec<u8>) -> Result<String> {
-    let base_dirs = BaseDirs::new().ok_or(anyhow!("Failed to get base dirs"))?;
-    let user_dirs = UserDirs::new().ok_or(anyhow!("Failed to get user dirs"))?;
-    let install_dir = base_dirs.data_local_dir().join("TinyWiiBackupManager");
-    let install_dir_str = install_dir
-        .to_str()
-        .ok_or(anyhow!("Failed to get install dir"))?;
-    let exe_path = install_dir.join("TinyWiiBackupManager.exe");
-    let exe_path_str = exe_path.to_str().ok_or(anyhow!("Failed to get exe path"))?;
-    let uninstaller_path = install_dir.join("uninstall.ps1");
-    let uninstaller_path_str = uninstaller_path
-        .to_str()
-        .ok_or(anyhow!("Failed to get uninstaller path"))?;
-    let desktop_dir = user_dirs
-        .desktop_dir()
-        .ok_or(anyhow!("Failed to get desktop dir"))?;
+use mslnk::{MSLinkError, ShellLink};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::ops::ControlFlow;
+use std::os::windows::{ffi::OsStrExt, process::CommandExt};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, mpsc};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{env, fmt, fs, io::Cursor, process::Command, thread};
+use toml_edit::{DocumentMut, Item, value};
+use windows::Win32::{
+    Networking::NetworkListManager::{
+        INetworkCostManager, INetworkListManager, NLM_CONNECTION_COST_FIXED,
+        NLM_CONNECTION_COST_OVERDATALIMIT, NLM_CONNECTION_COST_ROAMING,
+        NLM_CONNECTION_COST_VARIABLE, NetworkListManager,
+    },
+    System::Com::{
+        CLSCTX_ALL, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED, CoCreateInstance, CoInitializeEx,
+        CoUninitialize, IPersistFile, STGM_READ,
+    },
+    UI::Shell::{IShellLinkW, SLGP_RAWPATH},
+};
+use windows::core::{Interface, PCWSTR};
+use windows_registry::{CURRENT_USER, Key, LOCAL_MACHINE, Type};
+use windows_sys::Win32::{
+    Foundation::{
+        CloseHandle, ERROR_SERVICE_DISABLED, FreeLibrary, INVALID_HANDLE_VALUE, SYSTEMTIME,
+    },
+    Storage::FileSystem::{
+        GetDiskFreeSpaceExW, GetFileVersionInfoSizeW, GetFileVersionInfoW, VS_FIXEDFILEINFO,
+        VerQueryValueW,
+    },
+    System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, PROCESSENTRY32W, Process32FirstW, Process32NextW,
+        TH32CS_SNAPPROCESS,
+    },
+    System::LibraryLoader::{GetModuleHandleW, GetProcAddress, LoadLibraryW},
+    System::Restore::{
+        APPLICATION_INSTALL, BEGIN_SYSTEM_CHANGE, END_SYSTEM_CHANGE, MAX_DESC_W, RESTOREPOINTINFOW,
+        STATEMGRSTATUS,
+    },
+    System::SystemInformation::{
+        GetLocalTime, GetNativeSystemInfo, IMAGE_FILE_MACHINE, IMAGE_FILE_MACHINE_AMD64,
+        IMAGE_FILE_MACHINE_ARM64, IMAGE_FILE_MACHINE_I386, PROCESSOR_ARCHITECTURE_AMD64,
+        PROCESSOR_ARCHITECTURE_ARM64, PROCESSOR_ARCHITECTURE_INTEL, SYSTEM_INFO,
+    },
+    System::Threading::{
+        GetCurrentProcess, OpenProcess, PROCESS_SYNCHRONIZE, PROCESS_TERMINATE, TerminateProcess,
+        WaitForSingleObject,
+    },
+    UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW},
+    UI::Shell::{IsUserAnAdmin, SHCNE_ASSOCCHANGED, SHCNF_IDLIST, SHChangeNotify, ShellExecuteW},
+    UI::WindowsAndMessaging::{
+        GetSystemMetrics, HWND_BROADCAST, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
+        SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN, SMTO_ABORTIFHUNG, SPI_GETHIGHCONTRAST, SW_SHOWNORMAL,
+        SendMessageTimeoutW, SystemParametersInfoW, WM_SETTINGCHANGE,
+    },
+};
+use zip::{ZipArchive, result::ZipError};
 
-    // Open the archive
-    let cursor = Cursor::new(bytes);
-    let mut archive = ZipArchive::new(cursor)?;
+const UNINSTALL_KEY: &str =
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\TinyWiiBackupManager";
 
-    // Remove existing install
-    if install_dir.exists() {
-        fs::remove_dir_all(&install_dir)?;
-        fs::create_dir(&install_dir)?;
-    } else {
-        fs::create_dir_all(&install_dir)?;
-    }
+const CURRENT_VERSION_KEY: &str = "SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion";
 
-    // Extract the dist .zip into the install dir
-    let mut archived_exe = archive.by_name("TinyWiiBackupManager.exe")?;
-    let mut file = File::create(install_dir.join("TinyWiiBackupManager.exe"))?;
-    io::copy(&mut archived_exe, &mut file)?;
+const PERSONALIZE_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize";
 
-    // Write the uninstaller script
-    fs::write(&uninstaller_path, UNINSTALL_PS1)?;
+const CLASSES_KEY: &str = "Software\\Classes";
 
-    // Create desktop shortcut
-    let desktop_shortcut_path = desktop_dir.join("TinyWiiBackupManager.lnk");
-    if desktop_shortcut_path.exists() {
-        fs::remove_file(&desktop_shortcut_path)?;
-    }
-    let mut sl = ShellLink::new(&exe_path)?;
-    sl.set_working_dir(install_dir.to_str().map(String::from));
-    sl.set_icon_location(exe_path.to_str().map(String::from));
-    sl.set_name(Some("TinyWiiBackupManager".to_string()));
-    sl.create_lnk(&desktop_shortcut_path)?;
-
-    // Create start menu shortcut
-    let start_menu_dir = base_dirs
-        .data_dir()
-        .join("Microsoft\\Windows\\Start Menu\\Programs\\TinyWiiBackupManager");
-    if start_menu_dir.exists() {
-        fs::remove_dir_all(&start_menu_dir)?;
-    }
-    fs::create_dir_all(&start_menu_dir)?;
-    let start_menu_shortcut_path = start_menu_dir.join("TinyWiiBackupManager.lnk");
-    fs::copy(&desktop_shortcut_path, &start_menu_shortcut_path)?;
-
-    // Write windows registry keys
-    let key = CURRENT_USER
-        .create("Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\TinyWiiBackupManager")?;
-
-    let uninstall_cmd = format!(
-        "powershell.exe -ExecutionPolicy Bypass -WindowStyle Hidden -File \"{}\"",
-        uninstaller_path_str
-    );
+const ENVIRONMENT_KEY: &str = "Environment";
 
-    key.set_string("DisplayName", "TinyWiiBackupManager")?;
-    key.set_string("DisplayVersion", &version)?;
-    key.set_string("Publisher", "Manuel Quarneti")?;
-    key.set_string("InstallLocation", install_dir_str)?;
-    key.set_string("DisplayIcon", exe_path_str)?;
-    key.set_string("UninstallString", &uninstall_cmd)?;
-    key.set_u32("NoModify", 1)?;
-    key.set_u32("NoRepair", 1)?;
+/// Where the system PATH lives, under HKLM
+const MACHINE_ENVIRONMENT_KEY: &str =
+    "SYSTEM\\CurrentControlSet\\Control\\Session Manager\\Environment";
 
-    Ok(version)
-}
+/// Extension, ProgID and description of the file types we can open
+const FILE_ASSOCIATIONS: [(&str, &str, &str); 2] = [
+    (".wbfs", "TinyWiiBackupManager.wbfs", "Wii backup (WBFS)"),
+    (".iso", "TinyWiiBackupManager.iso", "Wii backup (ISO)"),
+];
 
-pub fn is_installed() -> Result<bool> {
-    let base_dirs = BaseDirs::new().ok_or(anyhow!("Failed to get base dirs"))?;
-    let install_dir = base_dirs.data_local_dir().join("TinyWiiBackupManager");
+/// Programs started at logon, always the current user's so others aren't opted in
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
 
-    Ok(install_dir.exists())
-}
+/// The value under RUN_KEY
+const RUN_VALUE: &str = "TinyWiiBackupManager";
 
-pub async fn download(version: String, os: Os, arch: Arch) -> Result<(String, Vec<u8>)> {
-    let url = format!(
-        "https://github.com/mq1/TinyWiiBackupManager/releases/download/v{}/TinyWiiBackupManager-v{}-{}-{}.zip",
-        &version,
-        &version,
-        os.as_str(),
-        arch.as_str()
-    );
+/// What went wrong, so the UI can offer the right way out
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum InstallError {
+    #[error("{0}")]
+    Network(String),
+    #[error("Checksum mismatch (expected {expected}, got {actual})")]
+    Checksum { expected: String, actual: String },
+    #[error("Failed to extract the archive: {0}")]
+    Unzip(String),
+    #[error("{0}")]
+    Io(String),
+    #[error("Failed to update the registry: {0}")]
+    Registry(String),
+    #[error("Permission denied: {0}")]
+    Permission(String),
+    /// The release file that doesn't exist
+    #[error("{0} was not found on the server")]
+    NotFound(String),
+    /// Minutes until the limit resets, if GitHub said
+    #[error("GitHub rate limit reached, try again {}", match .0 {
+        Some(minutes) => format!("in {} minutes", minutes),
+        None => "later".to_string(),
+    })]
+    RateLimited(Option<u64>),
+    #[error("A file is in use, please close TinyWiiBackupManager and try again")]
+    InUse,
+    /// A step failed midway, and what was done before it got undone where possible
+    #[error("\"{failed}\" failed: {error}{}", if .not_undone.is_empty() {
+        ", all changes were rolled back".to_string()
+    } else {
+        format!(", could not {}", .not_undone.join(", "))
+    })]
+    RolledBack {
+        failed: String,
+        error: Box<InstallError>,
+        not_undone: Vec<String>,
+    },
+    /// The release isn't signed with our key, or isn't signed at all
+    #[cfg(feature = "signature")]
+    #[error("The download's signature could not be verified ({0}), it may have been tampered with")]
+    Signature(String),
+    /// Far smaller than any release, so an error or login page rather than the app
+    #[error("The download is only {0} bytes, which can't be TinyWiiBackupManager")]
+    DownloadTooSmall(u64),
+    /// Over TWBM_MAX_DOWNLOAD_MB, as the server announced it or as it came in
+    #[error("The download is larger than the {limit} MiB limit ({size} bytes)")]
+    DownloadTooLarge { size: u64, limit: u64 },
+    #[error("The download isn't a zip archive")]
+    NotAZip,
+    #[error("Cancelled")]
+    Cancelled,
+    #[error("{0}")]
+    Other(String),
+}
 
-    let bytes = minreq::get(&url).send()?.into_bytes();
+impl InstallError {
+    /// The error that started it all, looking through rollbacks
+    pub fn root(&self) -> &InstallError {
+        match self {
+            InstallError::RolledBack { error, .. } => error.root(),
+            error => error,
+        }
+    }
+}
 
-    Ok((version, bytes))
+impl From<minreq::Error> for InstallError {
+    fn from(e: minreq::Error) -> Self {
+        network_error(e)
+    }
 }
 
-pub async fn download_to_dir(
-    version: String,
-    os: Os,
-    arch: Arch,
-    dest_dir: PathBuf,
-) -> Result<(String, PathBuf)> {
-    let (version, bytes) = download(version, os, arch).await?;
-    let dest_path = dest_dir.join(format!("TinyWiiBackupManager-v{}-portable.exe", version));
+impl From<io::Error> for InstallError {
+    fn from(e: io::Error) -> Self {
+        // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+        if matches!(e.raw_os_error(), Some(32 | 33)) {
+            return InstallError::InUse;
+        }
 
-    let cursor = Cursor::new(bytes);
-    let mut archive = ZipArchive::new(cursor)?;
+        match e.kind() {
+            io::ErrorKind::PermissionDenied => InstallError::Permission(e.to_string()),
+            _ => InstallError::Io(e.to_string()),
+        }
+    }
+}
 
-    if dest_path.exists() {
-        fs::remove_file(&dest_path)?;
+impl From<ZipError> for InstallError {
+    fn from(e: ZipError) -> Self {
+        InstallError::Unzip(e.to_string())
     }
+}
 
-    let mut file = File::create(&dest_path)?;
-    let mut archived_exe = archive.by_name("TinyWiiBackupManager.exe")?;
-    io::copy(&mut archived_exe, &mut file)?;
+impl From<windows_result::Error> for InstallError {
+    fn from(e: windows_result::Error) -> Self {
+        InstallError::Registry(e.message())
+    }
+}
 
-    Ok((version, dest_path))
+impl From<MSLinkError> for InstallError {
+    fn from(e: MSLinkError) -> Self {
+        InstallError::Io(e.to_string())
+    }
 }
 
-pub async fn get_latest_version() -> Result<String> {
-    let version = minreq::get(
-        "https://github.com/mq1/TinyWiiBackupManager/releases/latest/download/version.txt",
-    )
-    .send()?
-    .as_str()?
-    .to_string();
+impl From<anyhow::Error> for InstallError {
+    fn from(e: anyhow::Error) -> Self {
+        InstallError::Other(e.to_string())
+    }
+}
 
-    Ok(version)
+/// Opt-in extras for an install, and the shortcuts that can be opted out of
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstallOptions {
+    pub desktop_shortcut: bool,
+    pub start_menu_shortcut: bool,
+    /// Pins the Start menu shortcut, so only goes with it
+    pub pin_to_taskbar: bool,
+    /// Open .wbfs files with TinyWiiBackupManager
+    pub associate_files: bool,
+    /// Also take over .iso, which other apps tend to want too
+    pub associate_iso: bool,
+    /// Append the install dir to the user PATH
+    pub add_to_path: bool,
+    /// Ask System Restore for a restore point before changing anything
+    pub create_restore_point: bool,
+    /// Start TinyWiiBackupManager when the current user logs on
+    pub run_on_startup: bool,
+    pub scope: Scope,
+    /// Only extract the files and mark the folder, with no shortcuts or registry keys.
+    /// Overrides everything above, and is never remembered.
+    pub portable: bool,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
-pub enum Os {
+impl Default for InstallOptions {
+    fn default() -> Self {
+        InstallOptions {
+            desktop_shortcut: true,
+            start_menu_shortcut: true,
+            pin_to_taskbar: false,
+            associate_files: false,
+            associate_iso: false,
+            add_to_path: false,
+            create_restore_point: false,
+            run_on_startup: false,
+            scope: Scope::default(),
+            portable: false,
+        }
+    }
+}
+
+/// Who an install is for, which decides where its files and registry keys go
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scope {
     #[default]
-    Windows,
-    Windows7,
+    CurrentUser,
+    /// Needs administrator rights
+    AllUsers,
 }
 
-impl Os {
+impl Scope {
+    pub const ALL: [Scope; 2] = [Scope::CurrentUser, Scope::AllUsers];
+
     pub fn as_str(&self) -> &'static str {
         match self {
-            Os::Windows => "windows",
-            Os::Windows7 => "windows7",
+            Scope::CurrentUser => "user",
+            Scope::AllUsers => "machine",
         }
     }
 
     pub fn as_display_str(&self) -> &'static str {
         match self {
-            Os::Windows => "Windows 10+",
-            Os::Windows7 => "Windows 7/8/8.1",
+            Scope::CurrentUser => "Current user",
+            Scope::AllUsers => "All users",
+        }
+    }
+
+    /// The hive the uninstall key, file associations and PATH go in
+    fn root(&self) -> &'static Key {
+        match self {
+            Scope::CurrentUser => CURRENT_USER,
+            Scope::AllUsers => LOCAL_MACHINE,
         }
     }
-}
 
-pub fn get_os() -> Result<Os> {
-    let key = LOCAL_MACHINE.open("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion")?;
-    let product_name = key.get_string("ProductName")?;
+    fn root_name(&self) -> &'static str {
+        match self {
+            Scope::CurrentUser => "HKCU",
+            Scope::AllUsers => "HKLM",
+        }
+    }
 
-    if product_name.contains("Windows 10") {
-        Ok(Os::Windows)
-    } else {
-        Ok(Os::Windows7)
+    fn environment_key(&self) -> &'static str {
+        match self {
+            Scope::CurrentUser => ENVIRONMENT_KEY,
+            Scope::AllUsers => MACHINE_ENVIRONMENT_KEY,
+        }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-pub enum Arch {
-    I686,
-    X86_64,
-    X86_64v3,
-    Aarch64,
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_display_str())
+    }
 }
 
-impl Arch {
-    pub fn as_str(&self) -> &'static str {
+/// Files the installer owns in the install dir, anything else there is user data.
+/// uninstall.ps1 is only left behind by older installers.
+const MANAGED_FILES: [&str; 5] = [
+    "TinyWiiBackupManager.exe",
+    "uninstall.exe",
+    "uninstall.ps1",
+    PORTABLE_MARKER,
+    BUNDLED_FILES_LIST,
+];
+
+/// The files a release ships next to the exe, one path relative to the install dir
+/// per line, so an update or the uninstaller knows to remove them too
+const BUNDLED_FILES_LIST: &str = "bundled-files.txt";
+
+/// Marks a portable install, whose uninstaller then only deletes its own folder
+const PORTABLE_MARKER: &str = "portable.txt";
+
+/// One step of an install, in the order `install` runs them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InstallAction {
+    /// Only a warning if it fails, and nothing to undo
+    CreateRestorePoint,
+    /// Removes the exe and uninstaller of a previous install, keeping user data
+    RemoveManagedFiles(PathBuf),
+    /// Carries the exe's path, anything bundled with it goes in the same dir
+    ExtractFiles(PathBuf),
+    CopyUninstaller(PathBuf),
+    CreateDesktopShortcut(PathBuf),
+    /// Leaves anything else in the Start menu folder alone
+    CreateStartMenuShortcut(PathBuf),
+    /// Carries the Start menu shortcut to pin
+    PinToTaskbar(PathBuf),
+    AddToPath(PathBuf, Scope),
+    RegisterFileAssociations {
+        include_iso: bool,
+        scope: Scope,
+    },
+    RunOnStartup,
+    WriteUninstallKeys(Scope),
+    WritePortableMarker(PathBuf),
+}
+
+impl InstallAction {
+    /// Shortcuts don't fail the install, as redirected or locked folders are common
+    fn is_shortcut(&self) -> bool {
+        matches!(
+            self,
+            InstallAction::CreateDesktopShortcut(_) | InstallAction::CreateStartMenuShortcut(_)
+        )
+    }
+
+    /// What the step is reported as, steps that take no time aren't
+    fn progress_event(&self) -> Option<ProgressEvent> {
         match self {
-            Arch::I686 => "x86",
-            Arch::X86_64 => "x86_64",
-            Arch::X86_64v3 => "x86_64-v3",
-            Arch::Aarch64 => "arm64",
+            InstallAction::CreateRestorePoint | InstallAction::RemoveManagedFiles(_) => None,
+            InstallAction::ExtractFiles(_)
+            | InstallAction::CopyUninstaller(_)
+            | InstallAction::WritePortableMarker(_) => Some(ProgressEvent::Extracting),
+            InstallAction::CreateDesktopShortcut(_)
+            | InstallAction::CreateStartMenuShortcut(_)
+            | InstallAction::PinToTaskbar(_) => Some(ProgressEvent::CreatingShortcuts),
+            InstallAction::AddToPath(..)
+            | InstallAction::RegisterFileAssociations { .. }
+            | InstallAction::RunOnStartup
+            | InstallAction::WriteUninstallKeys(_) => Some(ProgressEvent::WritingRegistry),
         }
     }
+}
 
-    pub fn as_display_str(&self) -> &'static str {
+impl fmt::Display for InstallAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Arch::I686 => "x86 (32-bit)",
-            Arch::X86_64 => "x86 (64-bit)",
-            Arch::X86_64v3 => "x86 (64-bit with AVX2 instructions)",
-            Arch::Aarch64 => "ARM64",
+            InstallAction::CreateRestorePoint => write!(f, "Create a system restore point"),
+            InstallAction::RemoveManagedFiles(dir) => {
+                write!(
+                    f,
+                    "Remove the previously installed files from {}",
+                    dir.display()
+                )
+            }
+            InstallAction::ExtractFiles(path) => write!(
+                f,
+                "Extract TinyWiiBackupManager.exe, and any files bundled with it, to {}",
+                path.parent().unwrap_or(path).display()
+            ),
+            InstallAction::CopyUninstaller(path) => {
+                write!(f, "Copy the uninstaller to {}", path.display())
+            }
+            InstallAction::CreateDesktopShortcut(path) => {
+                write!(f, "Create a desktop shortcut at {}", path.display())
+            }
+            InstallAction::CreateStartMenuShortcut(path) => {
+                write!(f, "Create a Start menu shortcut at {}", path.display())
+            }
+            InstallAction::PinToTaskbar(_) => {
+                write!(f, "Try to pin TinyWiiBackupManager to the taskbar")
+            }
+            InstallAction::AddToPath(dir, scope) => write!(
+                f,
+                "Add {} to {}",
+                dir.display(),
+                match scope {
+                    Scope::CurrentUser => "your PATH",
+                    Scope::AllUsers => "the system PATH",
+                }
+            ),
+            InstallAction::RegisterFileAssociations { include_iso, scope } => write!(
+                f,
+                "Open {} files with TinyWiiBackupManager ({}\\{})",
+                if *include_iso {
+                    ".wbfs and .iso"
+                } else {
+                    ".wbfs"
+                },
+                scope.root_name(),
+                CLASSES_KEY
+            ),
+            InstallAction::RunOnStartup => write!(
+                f,
+                "Start TinyWiiBackupManager with Windows (HKCU\\{})",
+                RUN_KEY
+            ),
+            InstallAction::WriteUninstallKeys(scope) => write!(
+                f,
+                "Register the install in Apps & features ({}\\{})",
+                scope.root_name(),
+                UNINSTALL_KEY
+            ),
+            InstallAction::WritePortableMarker(path) => {
+                write!(f, "Mark the install as portable ({})", path.display())
+            }
         }
     }
 }
 
-pub fn get_arch() -> Arch {
-    match env::var("PROCESSOR_ARCHITEW6432").as_deref() {
-        Ok("AMD64") => {
-            if std::is_x86_feature_detected!("avx2")
-                && std::is_x86_feature_detected!("fma")
-                && std::is_x86_feature_detected!("bmi2")
-            {
-                Arch::X86_64v3
-            } else {
-                Arch::X86_64
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Makes `install` only log what it would do, as with TWBM_INSTALLER_DRY_RUN=1
+pub fn set_dry_run(dry_run: bool) {
+    DRY_RUN.store(dry_run, Ordering::Relaxed);
+}
+
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+        || env::var("TWBM_INSTALLER_DRY_RUN").is_ok_and(|value| value == "1")
+}
+
+/// Everything `install` is going to do, also shown to the user to review first
+pub fn plan_install(install_dir: &Path, options: InstallOptions) -> Result<Vec<InstallAction>> {
+    // Catches a remembered or registry dir too, not only a freshly picked one
+    check_install_dir_length(install_dir)?;
+    let uninstaller_path = install_dir.join("uninstall.exe");
+
+    let mut actions = Vec::new();
+    if options.portable {
+        actions.push(InstallAction::RemoveManagedFiles(install_dir.to_path_buf()));
+        actions.push(InstallAction::ExtractFiles(
+            install_dir.join("TinyWiiBackupManager.exe"),
+        ));
+        actions.push(InstallAction::CopyUninstaller(uninstaller_path));
+        actions.push(InstallAction::WritePortableMarker(
+            install_dir.join(PORTABLE_MARKER),
+        ));
+
+        return Ok(actions);
+    }
+
+    if options.create_restore_point {
+        actions.push(InstallAction::CreateRestorePoint);
+    }
+    actions.push(InstallAction::RemoveManagedFiles(install_dir.to_path_buf()));
+    actions.push(InstallAction::ExtractFiles(
+        install_dir.join("TinyWiiBackupManager.exe"),
+    ));
+
+    // When repairing from "Apps & features" we are the uninstaller, which can stay as is
+    if !same_file(&env::current_exe()?, &uninstaller_path) {
+        actions.push(InstallAction::CopyUninstaller(uninstaller_path));
+    }
+
+    if options.desktop_shortcut {
+        actions.push(InstallAction::CreateDesktopShortcut(desktop_shortcut_path(
+            options.scope,
+        )?));
+    }
+    if options.start_menu_shortcut {
+        let start_menu_shortcut_path = start_menu_shortcut_path(options.scope)?;
+        actions.push(InstallAction::CreateStartMenuShortcut(
+            start_menu_shortcut_path.clone(),
+        ));
+        if options.pin_to_taskbar {
+            actions.push(InstallAction::PinToTaskbar(start_menu_shortcut_path));
+        }
+    }
+    if options.add_to_path {
+        actions.push(InstallAction::AddToPath(
+            install_dir.to_path_buf(),
+            options.scope,
+        ));
+    }
+    if options.associate_files {
+        actions.push(InstallAction::RegisterFileAssociations {
+            include_iso: options.associate_iso,
+            scope: options.scope,
+        });
+    }
+    if options.run_on_startup {
+        actions.push(InstallAction::RunOnStartup);
+    }
+    actions.push(InstallAction::WriteUninstallKeys(options.scope));
+
+    Ok(actions)
+}
+
+/// What `install` did, for the summary once it's done
+#[derive(Clone, Debug)]
+pub struct InstallReport {
+    pub version: String,
+    pub install_dir: PathBuf,
+    /// The steps that ran, in order. A restore point that failed isn't in there.
+    pub actions: Vec<InstallAction>,
+    /// Steps that failed without failing the install
+    pub warnings: Vec<String>,
+    /// From opening the archive to the last step, the download isn't included
+    pub elapsed: Duration,
+}
+
+/// A cancel from `on_progress` rolls back like a failed step
+pub async fn install(
+    version: String,
+    bytes: Vec<u8>,
+    install_dir: PathBuf,
+    options: InstallOptions,
+    mut on_progress: impl FnMut(ProgressEvent) -> ControlFlow<()>,
+) -> Result<InstallReport, InstallError> {
+    let started = Instant::now();
+    log::write(format!(
+        "Installing v{} to {}",
+        version,
+        install_dir.display()
+    ));
+
+    // Open the archive
+    let cursor = Cursor::new(bytes);
+    let mut archive = ZipArchive::new(cursor)?;
+    // Refuses entries that would land outside the install dir before anything is written
+    bundled_entries(&archive)?;
+
+    let actions = plan_install(&install_dir, options)?;
+    if is_dry_run() {
+        for action in &actions {
+            log::write(format!("Dry run, would: {}", action));
+        }
+
+        return Ok(InstallReport {
+            version,
+            install_dir,
+            actions,
+            warnings: Vec::new(),
+            elapsed: started.elapsed(),
+        });
+    }
+
+    let (done, mut warnings) = run_actions(
+        &actions,
+        &mut archive,
+        &version,
+        &install_dir,
+        &mut on_progress,
+    )?;
+
+    // A re-tagged release or a swapped asset would still match its own checksum
+    if !is_nightly(&version) {
+        match exe_file_version(&install_dir.join("TinyWiiBackupManager.exe")) {
+            Ok(file_version) if !file_version_matches(&file_version, &version) => {
+                let warning = format!(
+                    "The installed exe reports version {}, not v{} as requested",
+                    file_version, version
+                );
+                log::write(&warning);
+                warnings.push(warning);
             }
+            Ok(file_version) => log::write(format!("The exe reports version {}", file_version)),
+            Err(e) => log::write(format!("Could not read the exe's version: {}", e)),
         }
-        Ok("ARM64") => Arch::Aarch64,
-        _ => Arch::I686,
     }
+
+    // The previous files are only needed to roll back
+    let previous_list = backup_path(&install_dir.join(BUNDLED_FILES_LIST));
+    let mut previous = managed_paths(&install_dir);
+    previous.extend(listed_bundled_files(&install_dir, &previous_list));
+    for path in previous {
+        let _ = fs::remove_file(backup_path(&path));
+    }
+
+    let elapsed = started.elapsed();
+    log::write(format!("Installed in {:.1}s", elapsed.as_secs_f32()));
+
+    Ok(InstallReport {
+        version,
+        install_dir,
+        actions: done,
+        warnings,
+        elapsed,
+    })
 }
 
-pub fn launch_twbm() -> Result<()> {
-    let base_dirs = BaseDirs::new().ok_or(anyhow!("Failed to get base dirs"))?;
-    let install_dir = base_dirs.data_local_dir().join("TinyWiiBackupManager");
+/// Runs the steps in order, rolling back the ones done if one fails or `on_progress`
+/// cancels. Returns the steps that ran and warnings about the ones allowed to fail.
+fn run_actions(
+    actions: &[InstallAction],
+    archive: &mut ZipArchive<Cursor<Vec<u8>>>,
+    version: &str,
+    install_dir: &Path,
+    on_progress: &mut dyn FnMut(ProgressEvent) -> ControlFlow<()>,
+) -> Result<(Vec<InstallAction>, Vec<String>), InstallError> {
+    // Undo whatever was done if a step fails, newest first
+    let mut undos = Vec::new();
+    let mut warnings = Vec::new();
+    let mut done = Vec::new();
+    let mut failed_shortcuts = Vec::new();
+    let mut last_event = None;
+    for action in actions {
+        // Reported once per kind of step, as the first of them starts
+        let mut cancelled = false;
+        if let Some(event) = action.progress_event()
+            && last_event != Some(event)
+        {
+            last_event = Some(event);
+            cancelled = on_progress(event).is_break();
+        }
+
+        if let InstallAction::CreateRestorePoint = action {
+            let description = format!("Install TinyWiiBackupManager v{}", version);
+            match create_restore_point(&description) {
+                Ok(()) => done.push(action.clone()),
+                Err(e) => {
+                    log::write(format!("Could not create a restore point: {}", e));
+                    warnings.push(format!("Could not create a restore point: {}", e));
+                }
+            }
+            continue;
+        }
+
+        // Nothing to pin if its shortcut couldn't be created
+        if let InstallAction::PinToTaskbar(path) = action
+            && failed_shortcuts.contains(path)
+        {
+            continue;
+        }
+
+        // Logged before it runs, so a hang shows up as the last step in the log
+        let res = if cancelled {
+            Err(InstallError::Cancelled)
+        } else {
+            log::write(format!("Step: {}", action));
+            run_action(action, archive, version, install_dir, on_progress)
+        };
+
+        match res {
+            Ok(undo) => {
+                undos.extend(undo);
+                done.push(action.clone());
+            }
+            // A shortcut is a convenience, the app works without it
+            Err(error) if action.is_shortcut() && !matches!(error, InstallError::Cancelled) => {
+                log::write(format!("\"{}\" failed, continuing: {}", action, error));
+                warnings.push(format!(
+                    "\"{}\" failed: {}. Check that the folder isn't read-only or redirected to \
+                     OneDrive, or create a shortcut to TinyWiiBackupManager.exe in {} by hand",
+                    action,
+                    error,
+                    install_dir.display()
+                ));
+                if let InstallAction::CreateDesktopShortcut(path)
+                | InstallAction::CreateStartMenuShortcut(path) = action
+                {
+                    failed_shortcuts.push(path.clone());
+                }
+            }
+            Err(error) => {
+                log::write(format!("\"{}\" failed, rolling back: {}", action, error));
+
+                return Err(InstallError::RolledBack {
+                    failed: action.to_string(),
+                    error: Box::new(error),
+                    not_undone: roll_back(undos),
+                });
+            }
+        }
+    }
+
+    Ok((done, warnings))
+}
+
+/// Reverts a finished install step
+type Undo = Box<dyn FnOnce() -> Result<(), InstallError>>;
+
+/// Runs the undos newest first, returning what the ones that failed were to undo
+fn roll_back(undos: Vec<(String, Undo)>) -> Vec<String> {
+    let mut not_undone = Vec::new();
+    for (description, undo) in undos.into_iter().rev() {
+        if let Err(e) = undo() {
+            log::write(format!("Could not {}: {}", description, e));
+            not_undone.push(description);
+        }
+    }
+
+    not_undone
+}
+
+/// Where a file from the previous install is kept until the new one is in place
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".old");
+    PathBuf::from(backup)
+}
+
+/// Removes `path` on undo, or puts back what it held before
+fn restore_file(path: PathBuf, previous: Option<Vec<u8>>) -> Undo {
+    Box::new(move || {
+        match previous {
+            Some(previous) => fs::write(&path, previous)?,
+            None => fs::remove_file(&path)?,
+        }
+
+        Ok(())
+    })
+}
+
+fn run_action(
+    action: &InstallAction,
+    archive: &mut ZipArchive<Cursor<Vec<u8>>>,
+    version: &str,
+    install_dir: &Path,
+    on_progress: &mut dyn FnMut(ProgressEvent) -> ControlFlow<()>,
+) -> Result<Option<(String, Undo)>, InstallError> {
     let exe_path = install_dir.join("TinyWiiBackupManager.exe");
-    let exe_path_str = exe_path.to_str().ok_or(anyhow!("Failed to get exe path"))?;
+    let uninstaller_path = install_dir.join("uninstall.exe");
 
-    Command::new("cmd")
-        .args(["/C", "start", "/B", exe_path_str])
-        .current_dir(install_dir)
-        .creation_flags(0x08000000) // CREATE_NO_WINDOW (run invisibly)
-        .spawn()?;
+    let undo: Undo = match action {
+        // Done by install itself, as it isn't allowed to fail the install
+        InstallAction::CreateRestorePoint => return Ok(None),
+        InstallAction::RemoveManagedFiles(dir) => {
+            fs::create_dir_all(dir)?;
 
-    Ok(())
+            // Moved aside rather than deleted, so a failed update can put them back
+            let current_exe = env::current_exe()?;
+            let mut moved = Vec::new();
+            for path in managed_paths(dir) {
+                if path.exists() && !same_file(&current_exe, &path) {
+                    fs::rename(&path, backup_path(&path))?;
+                    moved.push(path);
+                }
+            }
+
+            let dir = dir.clone();
+            Box::new(move || {
+                for path in moved {
+                    fs::rename(backup_path(&path), path)?;
+                }
+
+                // Only goes if this was a fresh install
+                let _ = fs::remove_dir(dir);
+                Ok(())
+            })
+        }
+        InstallAction::ExtractFiles(path) => {
+            let entries = bundled_entries(archive)?;
+            let total = entries.len() + 1;
+
+            let index = find_exe(archive)?;
+            let mut archived_exe = archive.by_index(index)?;
+            let mut file = File::create(path)?;
+            // The previous exe is aside as .old, so a half-written one can just go
+            let size = match io::copy(&mut archived_exe, &mut file) {
+                Ok(size) => size,
+                Err(e) => {
+                    drop(file);
+                    let _ = fs::remove_file(path);
+                    return Err(e.into());
+                }
+            };
+            drop(file);
+            drop(archived_exe);
+            log::write(format!(
+                "Extracted TinyWiiBackupManager.exe ({} bytes)",
+                size
+            ));
+
+            // Don't register an install that won't run
+            if !looks_like_pe(path) {
+                let _ = fs::remove_file(path);
+                return Err(InstallError::Unzip(
+                    "TinyWiiBackupManager.exe in the archive isn't a valid Windows executable"
+                        .into(),
+                ));
+            }
+
+            let mut written = vec![path.clone()];
+            let res =
+                extract_bundled_files(archive, &entries, install_dir, &mut written, &mut |done| {
+                    on_progress(ProgressEvent::ExtractedFile { done, total })
+                });
+            let undo = remove_extracted_files(written, install_dir.to_path_buf());
+            if let Err(error) = res {
+                let _ = undo();
+                return Err(error);
+            }
+
+            undo
+        }
+        InstallAction::CopyUninstaller(path) => {
+            // Copy ourselves as the uninstaller, which is detected by name at startup
+            fs::copy(env::current_exe()?, path)?;
+            restore_file(path.clone(), None)
+        }
+        InstallAction::CreateDesktopShortcut(path) => {
+            let previous = fs::read(path).ok();
+            if previous.is_some() {
+                fs::remove_file(path)?;
+            }
+            let restore = restore_file(path.clone(), previous);
+            if let Err(error) = create_shortcut(path, &exe_path, install_dir) {
+                let _ = restore();
+                return Err(error);
+            }
+
+            restore
+        }
+        InstallAction::CreateStartMenuShortcut(path) => {
+            let previous = fs::read(path).ok();
+            if previous.is_some() {
+                fs::remove_file(path)?;
+            }
+            let dir = path
+                .parent()
+                .ok_or(anyhow!("Failed to get start menu dir"))?;
+            let restore = restore_file(path.clone(), previous);
+            // Puts back the old shortcut, the install carries on without a new one
+            if let Err(error) = fs::create_dir_all(dir)
+                .map_err(InstallError::from)
+                .and_then(|()| create_shortcut(path, &exe_path, install_dir))
+            {
+                let _ = restore();
+                return Err(error);
+            }
+
+            let dir = dir.to_path_buf();
+            Box::new(move || {
+                restore()?;
+
+                // Only goes if we created it
+                let _ = fs::remove_dir(dir);
+                Ok(())
+            })
+        }
+        InstallAction::PinToTaskbar(shortcut_path) => {
+            // Pinning is best-effort, recent Windows versions don't let apps do it
+            match pin_to_taskbar(shortcut_path) {
+                Ok(()) => log::write("Pinned to the taskbar"),
+                Err(e) => log::write(format!("Skipped pinning to the taskbar: {}", e)),
+            }
+
+            return Ok(None);
+        }
+        InstallAction::AddToPath(dir, scope) => {
+            let scope = *scope;
+            if installed_options(dir, scope).add_to_path {
+                return Ok(None);
+            }
+
+            log::write("Adding the install dir to PATH");
+            add_to_path(dir, scope)?;
+
+            let dir = dir.clone();
+            Box::new(move || remove_from_path(&dir, scope))
+        }
+        InstallAction::RegisterFileAssociations { include_iso, scope } => {
+            let scope = *scope;
+            let was_associated = installed_options(install_dir, scope).associate_files;
+
+            log::write("Registering file associations");
+            register_file_associations(&exe_path, *include_iso, scope)?;
+
+            if was_associated {
+                return Ok(None);
+            }
+            Box::new(move || unregister_file_associations(scope))
+        }
+        InstallAction::RunOnStartup => {
+            let previous = CURRENT_USER
+                .open(RUN_KEY)
+                .and_then(|key| key.get_string(RUN_VALUE))
+                .ok();
+
+            log::write("Adding TinyWiiBackupManager to the startup programs");
+            set_run_on_startup(true, &exe_path)?;
+
+            let exe_path = exe_path.clone();
+            Box::new(move || match previous {
+                Some(previous) => Ok(CURRENT_USER
+                    .create(RUN_KEY)?
+                    .set_string(RUN_VALUE, previous)?),
+                None => set_run_on_startup(false, &exe_path),
+            })
+        }
+        InstallAction::WriteUninstallKeys(scope) => {
+            let scope = *scope;
+            let had_key = scope.root().open(UNINSTALL_KEY).is_ok();
+
+            log::write("Writing the uninstall registry keys");
+            write_uninstall_keys(version, install_dir, &exe_path, &uninstaller_path, scope)?;
+
+            // It's the last step, nothing after it can fail
+            if had_key {
+                return Ok(None);
+            }
+            Box::new(move || Ok(scope.root().remove_tree(UNINSTALL_KEY)?))
+        }
+        InstallAction::WritePortableMarker(path) => {
+            let previous = fs::read(path).ok();
+            fs::write(
+                path,
+                format!(
+                    "TinyWiiBackupManager v{} portable install.\r\nThis folder can be moved anywhere, run uninstall.exe to delete it.\r\n",
+                    version
+                ),
+            )?;
+
+            restore_file(path.clone(), previous)
+        }
+    };
+
+    Ok(Some((format!("undo \"{}\"", action), undo)))
 }
 
-pub fn launch_twbm_portable(exe_path: PathBuf) -> Result<()> {
-    let parent = exe_path.parent().ok_or(anyhow!("Failed to get parent"))?;
-    let exe_path_str = exe_path.to_str().ok_or(anyhow!("Failed to get exe path"))?;
+/// The options the install in `install_dir` was made with, so a repair keeps them
+pub fn installed_options(install_dir: &Path, scope: Scope) -> InstallOptions {
+    let install_dir_str = install_dir.to_string_lossy();
+    let add_to_path = scope
+        .root()
+        .open(scope.environment_key())
+        .and_then(|key| key.get_string("Path"))
+        .is_ok_and(|path| {
+            path.split(';')
+                .any(|entry| is_path_entry_for(entry, &install_dir_str))
+        });
+    let has_prog_id = |prog_id: &str| {
+        scope
+            .root()
+            .open(format!("{}\\{}", CLASSES_KEY, prog_id))
+            .is_ok()
+    };
 
-    Command::new("cmd")
-        .args(["/C", "start", "/B", exe_path_str])
-        .current_dir(parent)
-        .creation_flags(0x08000000) // CREATE_NO_WINDOW (run invisibly)
-        .spawn()?;
+    let exists = |path: Result<PathBuf>| path.is_ok_and(|path| path.exists());
 
-    Ok(())
+    InstallOptions {
+        desktop_shortcut: exists(desktop_shortcut_path(scope)),
+        start_menu_shortcut: exists(start_menu_shortcut_path(scope)),
+        pin_to_taskbar: exists(taskbar_pin_path()),
+        associate_files: has_prog_id(FILE_ASSOCIATIONS[0].1),
+        associate_iso: has_prog_id(FILE_ASSOCIATIONS[1].1),
+        add_to_path,
+        create_restore_point: false,
+        run_on_startup: is_run_on_startup(&install_dir.join("TinyWiiBackupManager.exe")),
+        scope,
+        portable: install_dir.join(PORTABLE_MARKER).exists(),
+    }
+}
+
+/// Splits an archive entry name into a relative path, refusing anything that could
+/// point outside the install dir: "..", absolute paths and drive letters
+fn safe_relative_path(name: &str) -> Option<PathBuf> {
+    if name.starts_with(['/', '\\']) {
+        return None;
+    }
+
+    let mut path = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => return None,
+            part if part.contains(':') => return None,
+            part => path.push(part),
+        }
+    }
+
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+/// The archive's files besides the exe, with their path relative to the install dir.
+/// Releases ship them next to the exe, in whatever folder that is in the archive.
+fn bundled_entries<R: Read + io::Seek>(
+    archive: &ZipArchive<R>,
+) -> Result<Vec<(usize, PathBuf)>, InstallError> {
+    let exe_index = find_exe(archive)?;
+    let exe_path = archive
+        .name_for_index(exe_index)
+        .and_then(safe_relative_path)
+        .ok_or(InstallError::Unzip(
+            "TinyWiiBackupManager.exe in the archive points outside the install folder".into(),
+        ))?;
+    let root = exe_path.parent().unwrap_or(Path::new(""));
+
+    let mut entries = Vec::new();
+    for index in 0..archive.len() {
+        let Some(name) = archive.name_for_index(index) else {
+            continue;
+        };
+        if index == exe_index || name.ends_with(['/', '\\']) {
+            continue;
+        }
+
+        let path = safe_relative_path(name).ok_or_else(|| {
+            InstallError::Unzip(format!(
+                "\"{}\" in the archive points outside the install folder",
+                name
+            ))
+        })?;
+
+        // Our own files always come from the installer, not the archive
+        let is_managed = |relative: &Path| {
+            relative.to_str().is_some_and(|relative| {
+                MANAGED_FILES
+                    .iter()
+                    .any(|name| relative.eq_ignore_ascii_case(name))
+            })
+        };
+        match path.strip_prefix(root) {
+            Ok(relative) if !is_managed(relative) => entries.push((index, relative.to_path_buf())),
+            _ => log::write(format!("Skipping {} from the archive", name)),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Writes the bundled files and their list, pushing each path to `written` as it's created
+/// so a failure partway can still be cleaned up. `on_file` is told how many are done.
+fn extract_bundled_files(
+    archive: &mut ZipArchive<Cursor<Vec<u8>>>,
+    entries: &[(usize, PathBuf)],
+    install_dir: &Path,
+    written: &mut Vec<PathBuf>,
+    on_file: &mut dyn FnMut(usize) -> ControlFlow<()>,
+) -> Result<(), InstallError> {
+    if on_file(1).is_break() {
+        return Err(InstallError::Cancelled);
+    }
+
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    for (i, (index, relative)) in entries.iter().enumerate() {
+        let path = install_dir.join(relative);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut entry = archive.by_index(*index)?;
+        let mut file = File::create(&path)?;
+        written.push(path.clone());
+        io::copy(&mut entry, &mut file)?;
+        log::write(format!("Extracted {}", relative.display()));
+
+        if on_file(i + 2).is_break() {
+            return Err(InstallError::Cancelled);
+        }
+    }
+
+    let list_path = install_dir.join(BUNDLED_FILES_LIST);
+    let list = entries
+        .iter()
+        .filter_map(|(_, relative)| relative.to_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(&list_path, list)?;
+    written.push(list_path);
+
+    Ok(())
+}
+
+/// Removes what an extraction wrote, and the folders it made for it if they're now empty
+fn remove_extracted_files(written: Vec<PathBuf>, install_dir: PathBuf) -> Undo {
+    Box::new(move || {
+        for path in written.iter().rev() {
+            fs::remove_file(path)?;
+            remove_empty_parents(path, &install_dir);
+        }
+
+        Ok(())
+    })
+}
+
+/// Bundled files can be in subfolders, which go once the last file in them is removed
+fn remove_empty_parents(path: &Path, install_dir: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir
+        && current != install_dir
+        && current.starts_with(install_dir)
+        && fs::remove_dir(current).is_ok()
+    {
+        dir = current.parent();
+    }
+}
+
+/// The paths in a bundled files list, ignoring any line that points outside `install_dir`
+fn listed_bundled_files(install_dir: &Path, list_path: &Path) -> Vec<PathBuf> {
+    let Ok(list) = fs::read_to_string(list_path) else {
+        return Vec::new();
+    };
+
+    list.lines()
+        .filter_map(safe_relative_path)
+        .map(|relative| install_dir.join(relative))
+        .collect()
+}
+
+/// Everything an install put in `install_dir`: MANAGED_FILES and the bundled files listed there
+fn managed_paths(install_dir: &Path) -> Vec<PathBuf> {
+    let list_path = install_dir.join(BUNDLED_FILES_LIST);
+    let mut paths = listed_bundled_files(install_dir, &list_path);
+    paths.extend(MANAGED_FILES.iter().map(|name| install_dir.join(name)));
+
+    paths
+}
+
+/// The index of the first TinyWiiBackupManager.exe in the archive, at the root or in a subfolder
+fn find_exe<R: Read + io::Seek>(archive: &ZipArchive<R>) -> Result<usize, InstallError> {
+    archive
+        .file_names()
+        .find(|name| {
+            name.rsplit(['/', '\\'])
+                .next()
+                .is_some_and(|file_name| file_name == "TinyWiiBackupManager.exe")
+        })
+        .and_then(|name| archive.index_for_name(name))
+        .ok_or(InstallError::Unzip(
+            "The archive doesn't contain TinyWiiBackupManager.exe".into(),
+        ))
+}
+
+/// Registers the install in "Apps & features"
+pub fn write_uninstall_keys(
+    version: &str,
+    install_dir: &Path,
+    exe_path: &Path,
+    uninstaller_path: &Path,
+    scope: Scope,
+) -> Result<(), InstallError> {
+    let install_dir_str = install_dir
+        .to_str()
+        .ok_or(anyhow!("Failed to get install dir"))?;
+    let exe_path_str = exe_path.to_str().ok_or(anyhow!("Failed to get exe path"))?;
+    let uninstaller_path_str = uninstaller_path
+        .to_str()
+        .ok_or(anyhow!("Failed to get uninstaller path"))?;
+
+    let key = scope.root().create(UNINSTALL_KEY)?;
+
+    let uninstall_cmd = format!("\"{}\"", uninstaller_path_str);
+    let quiet_uninstall_cmd = format!("\"{}\" --uninstall", uninstaller_path_str);
+    let repair_cmd = format!("\"{}\" --repair", uninstaller_path_str);
+
+    key.set_string("DisplayName", "TinyWiiBackupManager")?;
+    key.set_string("DisplayVersion", version)?;
+    key.set_string("Publisher", "Manuel Quarneti")?;
+    key.set_string("InstallLocation", install_dir_str)?;
+    key.set_string("DisplayIcon", exe_path_str)?;
+    key.set_string("UninstallString", &uninstall_cmd)?;
+    key.set_string("QuietUninstallString", &quiet_uninstall_cmd)?;
+    key.set_string("RepairString", &repair_cmd)?;
+    key.set_string(
+        "URLInfoAbout",
+        "https://github.com/mq1/TinyWiiBackupManager",
+    )?;
+    key.set_string("InstallDate", install_date())?;
+    key.set_u32("EstimatedSize", estimated_size_kb(install_dir))?;
+    if let Ok(Some(hash)) = file_hash(exe_path) {
+        key.set_string("ExeSha256", hash)?;
+    }
+    key.set_u32("NoModify", 1)?;
+    key.set_u32("NoRepair", 0)?;
+
+    Ok(())
+}
+
+/// Whether a PATH entry points at `dir`, ignoring case and trailing backslashes
+fn is_path_entry_for(entry: &str, dir: &str) -> bool {
+    entry
+        .trim()
+        .trim_end_matches('\\')
+        .eq_ignore_ascii_case(dir.trim_end_matches('\\'))
+}
+
+/// Rewrites the user or system PATH, keeping it REG_EXPAND_SZ if it was, so %VARS% keep working
+fn update_path(
+    scope: Scope,
+    update: impl FnOnce(Vec<String>) -> Vec<String>,
+) -> Result<(), InstallError> {
+    let key = scope.root().create(scope.environment_key())?;
+    let is_expand = !matches!(key.get_type("Path"), Ok(Type::String));
+    let path = key.get_string("Path").unwrap_or_default();
+
+    let entries = path
+        .split(';')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(String::from)
+        .collect();
+    let new_path = update(entries).join(";");
+    if new_path == path {
+        return Ok(());
+    }
+
+    if is_expand {
+        key.set_expand_string("Path", &new_path)?;
+    } else {
+        key.set_string("Path", &new_path)?;
+    }
+
+    broadcast_environment_change();
+    Ok(())
+}
+
+pub fn add_to_path(dir: &Path, scope: Scope) -> Result<(), InstallError> {
+    let dir = dir.to_str().ok_or(anyhow!("Failed to get install dir"))?;
+
+    update_path(scope, |mut entries| {
+        if !entries.iter().any(|entry| is_path_entry_for(entry, dir)) {
+            entries.push(dir.to_string());
+        }
+        entries
+    })
+}
+
+/// Removes exactly our entry, leaving the rest of PATH untouched
+pub fn remove_from_path(dir: &Path, scope: Scope) -> Result<(), InstallError> {
+    let dir = dir.to_str().ok_or(anyhow!("Failed to get install dir"))?;
+
+    update_path(scope, |entries| {
+        entries
+            .into_iter()
+            .filter(|entry| !is_path_entry_for(entry, dir))
+            .collect()
+    })
+}
+
+/// Lets Explorer (and terminals started from it) see the new PATH without logging out
+fn broadcast_environment_change() {
+    let environment = wide("Environment");
+    unsafe {
+        SendMessageTimeoutW(
+            HWND_BROADCAST,
+            WM_SETTINGCHANGE,
+            0,
+            environment.as_ptr() as isize,
+            SMTO_ABORTIFHUNG,
+            5000,
+            ptr::null_mut(),
+        )
+    };
+}
+
+/// Adds or removes the current user's Run value for `exe_path`, ignored when removing
+pub fn set_run_on_startup(enabled: bool, exe_path: &Path) -> Result<(), InstallError> {
+    if enabled {
+        let exe_path_str = exe_path.to_str().ok_or(anyhow!("Failed to get exe path"))?;
+        CURRENT_USER
+            .create(RUN_KEY)?
+            .set_string(RUN_VALUE, format!("\"{}\"", exe_path_str))?;
+    } else if let Ok(key) = CURRENT_USER.create(RUN_KEY)
+        && key.get_string(RUN_VALUE).is_ok()
+    {
+        key.remove_value(RUN_VALUE)?;
+    }
+
+    Ok(())
+}
+
+/// Whether the Run value points at `exe_path`
+fn is_run_on_startup(exe_path: &Path) -> bool {
+    CURRENT_USER
+        .open(RUN_KEY)
+        .and_then(|key| key.get_string(RUN_VALUE))
+        .is_ok_and(|command| same_file(Path::new(command.trim_matches('"')), exe_path))
+}
+
+/// Makes double-clicking .wbfs (and .iso, if asked to) files open TinyWiiBackupManager
+pub fn register_file_associations(
+    exe_path: &Path,
+    include_iso: bool,
+    scope: Scope,
+) -> Result<(), InstallError> {
+    let exe_path_str = exe_path.to_str().ok_or(anyhow!("Failed to get exe path"))?;
+    let classes = scope.root().create(CLASSES_KEY)?;
+
+    for (extension, prog_id, description) in FILE_ASSOCIATIONS {
+        if extension == ".iso" && !include_iso {
+            continue;
+        }
+
+        let prog_id_key = classes.create(prog_id)?;
+        prog_id_key.set_string("", description)?;
+        prog_id_key
+            .create("DefaultIcon")?
+            .set_string("", format!("\"{}\",0", exe_path_str))?;
+        prog_id_key
+            .create("shell\\open\\command")?
+            .set_string("", format!("\"{}\" \"%1\"", exe_path_str))?;
+
+        let extension_key = classes.create(extension)?;
+        extension_key.set_string("", prog_id)?;
+        extension_key
+            .create("OpenWithProgids")?
+            .set_string(prog_id, "")?;
+    }
+
+    notify_associations_changed();
+    Ok(())
+}
+
+/// Removes our ProgIDs, leaving extensions that were since taken over by other apps alone
+pub fn unregister_file_associations(scope: Scope) -> Result<(), InstallError> {
+    let Ok(classes) = scope.root().open(CLASSES_KEY) else {
+        return Ok(());
+    };
+
+    for (extension, prog_id, _) in FILE_ASSOCIATIONS {
+        if let Ok(extension_key) = classes.open(extension) {
+            if extension_key
+                .get_string("")
+                .is_ok_and(|default| default == prog_id)
+            {
+                extension_key.remove_value("")?;
+            }
+            if let Ok(open_with) = extension_key.open("OpenWithProgids") {
+                let _ = open_with.remove_value(prog_id);
+            }
+        }
+
+        if classes.open(prog_id).is_ok() {
+            classes.remove_tree(prog_id)?;
+        }
+    }
+
+    notify_associations_changed();
+    Ok(())
+}
+
+/// Tells Explorer to pick up the new file icons and handlers
+fn notify_associations_changed() {
+    unsafe {
+        SHChangeNotify(
+            SHCNE_ASSOCCHANGED as i32,
+            SHCNF_IDLIST,
+            ptr::null(),
+            ptr::null(),
+        )
+    };
+}
+
+/// Whether we're running as the uninstall.exe copy inside the install dir
+pub fn is_uninstaller() -> bool {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_name().map(|name| name.to_ascii_lowercase()))
+        .is_some_and(|name| name == "uninstall.exe")
+}
+
+/// Removes the shortcuts, the registry key and the install dir.
+/// User data (anything we didn't install, plus the app's settings) is only removed if asked to.
+pub fn uninstall(is_uninstaller: bool, keep_user_data: bool) -> Result<(), InstallError> {
+    // A portable install changed nothing outside its folder
+    if is_uninstaller && let Some(portable_dir) = portable_dir() {
+        log::write(format!(
+            "Uninstalling the portable install in {}",
+            portable_dir.display()
+        ));
+        let running_exe = env::current_exe()?;
+        remove_install_dir(&portable_dir, Some(&running_exe), keep_user_data)?;
+        return schedule_self_delete(&running_exe);
+    }
+
+    let installed_scope = installed_scope();
+    if installed_scope.is_none() && !is_installed()? {
+        return Err(InstallError::Other(
+            "TinyWiiBackupManager is not installed".to_string(),
+        ));
+    }
+
+    let scope = installed_scope.unwrap_or_default();
+    if scope == Scope::AllUsers && !is_elevated() {
+        return Err(InstallError::Permission(
+            "TinyWiiBackupManager is installed for all users, uninstalling it requires administrator rights".to_string(),
+        ));
+    }
+
+    let install_dir = install_dir()?;
+    log::write(format!("Uninstalling from {}", install_dir.display()));
+
+    remove_shortcuts(
+        &desktop_shortcut_path(scope)?,
+        &start_menu_shortcut_path(scope)?,
+        &taskbar_pin_path()?,
+    )?;
+
+    // App data directory
+    let data_dir = data_dir()?;
+    if !keep_user_data && data_dir.exists() {
+        fs::remove_dir_all(&data_dir)?;
+    }
+
+    // Cached downloads, they're of no use anymore
+    if let Err(e) = clear_cache() {
+        log::write(format!("Failed to clear the download cache: {}", e));
+    }
+
+    // File associations, PATH and startup
+    unregister_file_associations(scope)?;
+    remove_from_path(&install_dir, scope)?;
+    set_run_on_startup(false, &install_dir.join("TinyWiiBackupManager.exe"))?;
+
+    // Registry
+    if installed_scope.is_some() {
+        scope.root().remove_tree(UNINSTALL_KEY)?;
+    }
+
+    // Install dir, the running uninstaller gets deleted after we exit
+    let running_exe = if is_uninstaller {
+        env::current_exe().ok()
+    } else {
+        None
+    };
+    remove_install_dir(&install_dir, running_exe.as_deref(), keep_user_data)?;
+
+    if let Some(running_exe) = running_exe {
+        schedule_self_delete(&running_exe)?;
+    }
+
+    Ok(())
+}
+
+/// Removes the desktop shortcut, the Start menu one and the taskbar pin, whichever exist.
+/// The Start menu folder goes too, unless the user put something else in there.
+fn remove_shortcuts(
+    desktop_shortcut_path: &Path,
+    start_menu_shortcut_path: &Path,
+    taskbar_pin_path: &Path,
+) -> Result<(), InstallError> {
+    for path in [
+        desktop_shortcut_path,
+        start_menu_shortcut_path,
+        taskbar_pin_path,
+    ] {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+
+    if let Some(start_menu_dir) = start_menu_shortcut_path.parent() {
+        let _ = fs::remove_dir(start_menu_dir);
+    }
+
+    Ok(())
+}
+
+/// The folder of the running uninstaller, if it's a portable install
+fn portable_dir() -> Option<PathBuf> {
+    let exe = env::current_exe().ok()?;
+    let dir = exe.parent()?;
+
+    dir.join(PORTABLE_MARKER)
+        .exists()
+        .then(|| dir.to_path_buf())
+}
+
+/// Deletes the running exe (and its dir, if that's then empty) once we've exited.
+/// Windows keeps running executables locked, so a detached cmd retries until the lock is gone.
+pub fn schedule_self_delete(path: &Path) -> Result<(), InstallError> {
+    let dir = path
+        .parent()
+        .ok_or(anyhow!("Failed to get the exe's dir"))?;
+    let path = path.to_str().ok_or(anyhow!("Failed to get exe path"))?;
+    let dir = dir.to_str().ok_or(anyhow!("Failed to get the exe's dir"))?;
+
+    // Gives up after about 10 minutes, rmdir only removes the dir if it's empty
+    let script = format!(
+        "for /L %i in (1,1,600) do (ping -n 2 127.0.0.1 >nul & del /F /Q \"{path}\" 2>nul & if not exist \"{path}\" (rmdir \"{dir}\" 2>nul & exit))"
+    );
+
+    Command::new("cmd.exe")
+        .arg("/C")
+        .raw_arg(script)
+        .current_dir(env::temp_dir())
+        .creation_flags(0x08000000 | 0x00000008) // CREATE_NO_WINDOW | DETACHED_PROCESS
+        .spawn()?;
+
+    Ok(())
+}
+
+fn remove_install_dir(
+    install_dir: &Path,
+    skip: Option<&Path>,
+    keep_user_data: bool,
+) -> Result<(), InstallError> {
+    if !install_dir.exists() {
+        return Ok(());
+    }
+
+    // Bundled files can be anywhere in there, their list says which are ours
+    if keep_user_data {
+        remove_managed_files(install_dir, skip)?;
+        let _ = fs::remove_dir(install_dir);
+
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(install_dir)? {
+        let path = entry?.path();
+        if skip.is_some_and(|skip| same_file(skip, &path)) {
+            continue;
+        }
+
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+    }
+
+    // Only fails if something was left behind, which is fine
+    let _ = fs::remove_dir(install_dir);
+
+    Ok(())
+}
+
+/// Process ids of every running TinyWiiBackupManager.exe
+fn running_pids() -> Vec<u32> {
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return Vec::new();
+    }
+
+    let mut pids = Vec::new();
+    let mut entry = PROCESSENTRY32W {
+        dwSize: size_of::<PROCESSENTRY32W>() as u32,
+        ..Default::default()
+    };
+    let mut ok = unsafe { Process32FirstW(snapshot, &mut entry) };
+    while ok != 0 {
+        let len = entry
+            .szExeFile
+            .iter()
+            .position(|c| *c == 0)
+            .unwrap_or(entry.szExeFile.len());
+        let name = String::from_utf16_lossy(&entry.szExeFile[..len]);
+        if name.eq_ignore_ascii_case("TinyWiiBackupManager.exe") {
+            pids.push(entry.th32ProcessID);
+        }
+
+        ok = unsafe { Process32NextW(snapshot, &mut entry) };
+    }
+
+    unsafe { CloseHandle(snapshot) };
+    pids
+}
+
+/// Whether TinyWiiBackupManager is running, which would keep its files locked
+pub fn is_running() -> bool {
+    !running_pids().is_empty()
+}
+
+/// Terminates every running TinyWiiBackupManager, waiting a bit for each to exit
+pub fn close_running() -> Result<(), InstallError> {
+    for pid in running_pids() {
+        log::write(format!("Terminating TinyWiiBackupManager (pid {})", pid));
+
+        let process = unsafe { OpenProcess(PROCESS_TERMINATE | PROCESS_SYNCHRONIZE, 0, pid) };
+        if process.is_null() {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        let ok = unsafe { TerminateProcess(process, 1) };
+        if ok != 0 {
+            unsafe { WaitForSingleObject(process, 5000) };
+        }
+        let e = io::Error::last_os_error();
+        unsafe { CloseHandle(process) };
+
+        if ok == 0 {
+            return Err(e.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the internet connection is metered (mobile data, a data limit, roaming).
+/// Assumes it isn't if Windows can't tell, like on Windows 7.
+pub fn is_metered_connection() -> bool {
+    // The COM flavour of NetworkInformation's ConnectionCost, the WinRT one doesn't load on 7
+    let cost = unsafe {
+        let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+
+        let cost =
+            CoCreateInstance::<_, INetworkCostManager>(&NetworkListManager, None, CLSCTX_ALL)
+                .and_then(|manager| {
+                    let mut cost = 0;
+                    manager.GetCost(&mut cost, ptr::null()).map(|()| cost)
+                });
+
+        if initialized {
+            CoUninitialize();
+        }
+
+        cost
+    };
+
+    match cost {
+        Ok(cost) => {
+            let metered_flags = NLM_CONNECTION_COST_FIXED.0
+                | NLM_CONNECTION_COST_VARIABLE.0
+                | NLM_CONNECTION_COST_OVERDATALIMIT.0
+                | NLM_CONNECTION_COST_ROAMING.0;
+            let metered = cost & metered_flags as u32 != 0;
+
+            if metered {
+                log::write(format!("Metered connection (cost flags {:#x})", cost));
+            }
+
+            metered
+        }
+        Err(e) => {
+            log::write(format!("Could not get the connection cost: {}", e));
+            false
+        }
+    }
+}
+
+/// Whether Windows thinks there's an internet connection. Assumes there is when it can't tell,
+/// so a failure is then blamed on the server rather than on being offline.
+pub fn is_online() -> bool {
+    let connected = unsafe {
+        let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+
+        let connected =
+            CoCreateInstance::<_, INetworkListManager>(&NetworkListManager, None, CLSCTX_ALL)
+                .and_then(|manager| manager.IsConnectedToInternet())
+                .map(|connected| connected.0 != 0);
+
+        if initialized {
+            CoUninitialize();
+        }
+
+        connected
+    };
+
+    match connected {
+        Ok(connected) => {
+            if !connected {
+                log::write("Windows reports no internet connection");
+            }
+            connected
+        }
+        Err(e) => {
+            log::write(format!("Could not get the connectivity: {}", e));
+            true
+        }
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// A folder from the environment, for the all-users ones `directories` doesn't know about
+fn env_dir(name: &str) -> Result<PathBuf> {
+    env::var_os(name)
+        .map(PathBuf::from)
+        .ok_or(anyhow!("%{}% is not set", name))
+}
+
+fn desktop_shortcut_path(scope: Scope) -> Result<PathBuf> {
+    let desktop_dir = match scope {
+        Scope::CurrentUser => {
+            let user_dirs = UserDirs::new().ok_or(anyhow!("Failed to get user dirs"))?;
+            user_dirs
+                .desktop_dir()
+                .ok_or(anyhow!("Failed to get desktop dir"))?
+                .to_path_buf()
+        }
+        Scope::AllUsers => env_dir("PUBLIC")?.join("Desktop"),
+    };
+
+    Ok(desktop_dir.join("TinyWiiBackupManager.lnk"))
+}
+
+fn start_menu_shortcut_path(scope: Scope) -> Result<PathBuf> {
+    let base_dir = match scope {
+        Scope::CurrentUser => {
+            let base_dirs = BaseDirs::new().ok_or(anyhow!("Failed to get base dirs"))?;
+            base_dirs.data_dir().to_path_buf()
+        }
+        Scope::AllUsers => env_dir("ProgramData")?,
+    };
+
+    Ok(base_dir.join(
+        "Microsoft\\Windows\\Start Menu\\Programs\\TinyWiiBackupManager\\TinyWiiBackupManager.lnk",
+    ))
+}
+
+/// Recreates the desktop and Start menu shortcuts of the existing install, e.g. after
+/// the install dir was moved by hand
+pub fn create_shortcuts(exe_path: &Path, install_dir: &Path) -> Result<(), InstallError> {
+    let scope = installed_scope().unwrap_or_default();
+
+    for path in [
+        desktop_shortcut_path(scope)?,
+        start_menu_shortcut_path(scope)?,
+    ] {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        create_shortcut(&path, exe_path, install_dir)?;
+    }
+
+    log::write("Recreated the shortcuts");
+    Ok(())
+}
+
+/// Whether both the desktop and the Start menu shortcut are there
+pub fn shortcuts_exist() -> bool {
+    let scope = installed_scope().unwrap_or_default();
+
+    [
+        desktop_shortcut_path(scope),
+        start_menu_shortcut_path(scope),
+    ]
+    .into_iter()
+    .all(|path| path.is_ok_and(|path| path.exists()))
+}
+
+/// Whether the shortcuts that are there still open the installed exe.
+/// Ones the user deleted don't count as broken.
+pub fn shortcuts_valid() -> bool {
+    let Ok(exe_path) = install_dir().map(|dir| dir.join("TinyWiiBackupManager.exe")) else {
+        return true;
+    };
+    let scope = installed_scope().unwrap_or_default();
+
+    [
+        desktop_shortcut_path(scope),
+        start_menu_shortcut_path(scope),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|path| path.exists())
+    .all(|path| match shortcut_target(&path) {
+        Ok(target) => same_file(&target, &exe_path),
+        Err(e) => {
+            log::write(format!("Could not read {}: {}", path.display(), e));
+            false
+        }
+    })
+}
+
+/// Where a .lnk points, read through the shell like Explorer does
+fn shortcut_target(path: &Path) -> Result<PathBuf> {
+    let path = wide_path(path);
+
+    let target = unsafe {
+        let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+
+        let target = CoCreateInstance::<_, IShellLinkW>(
+            &windows::Win32::UI::Shell::ShellLink,
+            None,
+            CLSCTX_INPROC_SERVER,
+        )
+        .and_then(|link| {
+            link.cast::<IPersistFile>()?
+                .Load(PCWSTR(path.as_ptr()), STGM_READ)?;
+
+            let mut target = [0u16; MAX_PATH];
+            link.GetPath(&mut target, ptr::null_mut(), SLGP_RAWPATH.0 as u32)?;
+            Ok(target)
+        });
+
+        if initialized {
+            CoUninitialize();
+        }
+
+        target?
+    };
+
+    let len = target.iter().position(|c| *c == 0).unwrap_or(target.len());
+    Ok(PathBuf::from(String::from_utf16_lossy(&target[..len])))
+}
+
+fn create_shortcut(path: &Path, exe_path: &Path, install_dir: &Path) -> Result<(), InstallError> {
+    let mut sl = ShellLink::new(exe_path)?;
+    sl.set_working_dir(install_dir.to_str().map(String::from));
+    sl.set_icon_location(exe_path.to_str().map(String::from));
+    sl.set_name(Some("TinyWiiBackupManager".to_string()));
+    sl.create_lnk(path)?;
+
+    Ok(())
+}
+
+/// Where Explorer keeps the shortcut once it's pinned
+fn taskbar_pin_path() -> Result<PathBuf> {
+    let base_dirs = BaseDirs::new().ok_or(anyhow!("Failed to get base dirs"))?;
+
+    Ok(base_dirs.data_dir().join(
+        "Microsoft\\Internet Explorer\\Quick Launch\\User Pinned\\TaskBar\\TinyWiiBackupManager.lnk",
+    ))
+}
+
+/// Invokes the shell's "taskbarpin" verb on the shortcut, which Windows 10 1809+ refuses
+fn pin_to_taskbar(shortcut_path: &Path) -> Result<()> {
+    let verb = wide("taskbarpin");
+    let shortcut = shortcut_path
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect::<Vec<_>>();
+
+    let res = unsafe {
+        ShellExecuteW(
+            ptr::null_mut(),
+            verb.as_ptr(),
+            shortcut.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+
+    if res as usize <= 32 {
+        bail!("not supported on this version of Windows");
+    }
+
+    Ok(())
+}
+
+/// Where TinyWiiBackupManager keeps its settings
+fn data_dir() -> Result<PathBuf> {
+    let base_dirs = BaseDirs::new().ok_or(anyhow!("Failed to get base dirs"))?;
+
+    Ok(base_dirs.data_dir().join("mq1\\TinyWiiBackupManager"))
+}
+
+/// The current local date as YYYYMMDD
+fn install_date() -> String {
+    let mut now = SYSTEMTIME::default();
+    unsafe { GetLocalTime(&mut now) };
+
+    format!("{:04}{:02}{:02}", now.wYear, now.wMonth, now.wDay)
+}
+
+/// When the existing install was made (or last updated), as YYYY-MM-DD
+pub fn installed_date() -> Option<String> {
+    let date = uninstall_key()?.get_string("InstallDate").ok()?;
+    if date.len() != 8 || !date.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]))
+}
+
+/// The size of the installed files in KB, rounded up
+fn estimated_size_kb(install_dir: &Path) -> u32 {
+    let bytes = managed_paths(install_dir)
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum::<u64>();
+
+    bytes.div_ceil(1024) as u32
+}
+
+/// The file version from the exe's version resource, like "1.2.3.0"
+pub fn exe_file_version(path: &Path) -> Result<String> {
+    let file = wide_path(path);
+    let size = unsafe { GetFileVersionInfoSizeW(file.as_ptr(), ptr::null_mut()) };
+    if size == 0 {
+        bail!("{} has no version resource", path.display());
+    }
+
+    let mut data = vec![0u8; size as usize];
+    if unsafe { GetFileVersionInfoW(file.as_ptr(), 0, size, data.as_mut_ptr().cast()) } == 0 {
+        bail!(
+            "Failed to read the version resource: {}",
+            io::Error::last_os_error()
+        );
+    }
+
+    // The root block is the language-independent VS_FIXEDFILEINFO
+    let mut info = ptr::null_mut();
+    let mut len = 0;
+    let root = wide("\\");
+    let found = unsafe { VerQueryValueW(data.as_ptr().cast(), root.as_ptr(), &mut info, &mut len) };
+    if found == 0 || info.is_null() || (len as usize) < size_of::<VS_FIXEDFILEINFO>() {
+        bail!("The version resource has no fixed file info");
+    }
+
+    let info = unsafe { &*(info as *const VS_FIXEDFILEINFO) };
+    Ok(format!(
+        "{}.{}.{}.{}",
+        info.dwFileVersionMS >> 16,
+        info.dwFileVersionMS & 0xffff,
+        info.dwFileVersionLS >> 16,
+        info.dwFileVersionLS & 0xffff
+    ))
+}
+
+/// Whether an exe's 4-part file version is the release `version`. Missing parts count
+/// as 0 and a pre-release suffix is ignored, as file versions can't carry one.
+fn file_version_matches(file_version: &str, version: &str) -> bool {
+    let (Some((file_version, _)), Some((version, _))) =
+        (parse_version(file_version), parse_version(version))
+    else {
+        return false;
+    };
+
+    let len = file_version.len().max(version.len());
+    (0..len)
+        .all(|i| file_version.get(i).copied().unwrap_or(0) == version.get(i).copied().unwrap_or(0))
+}
+
+/// Whether the file has the "MZ" DOS header every Windows executable starts with, and the
+/// "PE" signature it points to. An empty or truncated file has neither.
+pub fn looks_like_pe(path: &Path) -> bool {
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+
+    let mut dos_header = [0; 64];
+    if file.read_exact(&mut dos_header).is_err() || &dos_header[..2] != b"MZ" {
+        return false;
+    }
+
+    // e_lfanew, the offset of the PE header
+    let offset = u32::from_le_bytes([
+        dos_header[60],
+        dos_header[61],
+        dos_header[62],
+        dos_header[63],
+    ]);
+    let mut signature = [0; 4];
+
+    file.seek(io::SeekFrom::Start(offset.into())).is_ok()
+        && file.read_exact(&mut signature).is_ok()
+        && &signature == b"PE\0\0"
+}
+
+fn remove_managed_files(install_dir: &Path, skip: Option<&Path>) -> Result<(), InstallError> {
+    for path in managed_paths(install_dir) {
+        if skip.is_some_and(|skip| same_file(skip, &path)) {
+            continue;
+        }
+
+        if path.exists() {
+            fs::remove_file(&path)?;
+            remove_empty_parents(&path, install_dir);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn default_install_dir(scope: Scope) -> Result<PathBuf> {
+    match scope {
+        Scope::CurrentUser => {
+            let base_dirs = BaseDirs::new().ok_or(anyhow!("Failed to get base dirs"))?;
+            Ok(base_dirs.data_local_dir().join("TinyWiiBackupManager"))
+        }
+        // The 64-bit Program Files, even if we happen to be a 32-bit build
+        Scope::AllUsers => Ok(env_dir("ProgramW6432")
+            .or_else(|_| env_dir("ProgramFiles"))?
+            .join("TinyWiiBackupManager")),
+    }
+}
+
+/// Where the existing install registered itself, per-user installs first
+pub fn installed_scope() -> Option<Scope> {
+    Scope::ALL
+        .into_iter()
+        .find(|scope| scope.root().open(UNINSTALL_KEY).is_ok())
+}
+
+/// The existing install's uninstall key
+fn uninstall_key() -> Option<Key> {
+    installed_scope().and_then(|scope| scope.root().open(UNINSTALL_KEY).ok())
+}
+
+/// The directory of the existing install, falling back to the per-user default
+pub fn install_dir() -> Result<PathBuf> {
+    let previous = uninstall_key().and_then(|key| key.get_string("InstallLocation").ok());
+
+    match previous {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => default_install_dir(Scope::CurrentUser),
+    }
+}
+
+/// Whether we're running with administrator rights, which all-users installs need
+pub fn is_elevated() -> bool {
+    unsafe { IsUserAnAdmin() != 0 }
+}
+
+/// Whether we can create files in `dir`, or in its closest existing ancestor
+pub fn can_write_to(dir: &Path) -> bool {
+    let Some(existing) = dir.ancestors().find(|dir| dir.is_dir()) else {
+        return false;
+    };
+
+    let probe = existing.join(".TinyWiiBackupManagerInstaller-write-test");
+    match File::create(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Roughly what an install takes: the downloaded zip in memory plus the extracted exe
+pub const REQUIRED_SPACE: u64 = 100 * 1024 * 1024;
+
+/// Bytes available to the current user on the volume `dir` is (or would be) on
+pub fn free_space(dir: &Path) -> Result<u64> {
+    let existing = dir
+        .ancestors()
+        .find(|dir| dir.is_dir())
+        .ok_or(anyhow!("{} is not on an existing drive", dir.display()))?;
+    let existing = wide_path(existing);
+
+    let mut free = 0;
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            existing.as_ptr(),
+            &mut free,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    Ok(free)
+}
+
+/// Starts an elevated copy of the installer, preselecting `install_dir` and `scope`
+pub fn relaunch_elevated(install_dir: &Path, scope: Scope) -> Result<()> {
+    let exe = env::current_exe()?;
+    let mut params = format!("--install-dir \"{}\"", install_dir.display());
+    if scope == Scope::AllUsers {
+        params.push_str(" --all-users");
+    }
+
+    let verb = wide("runas");
+    let exe = exe
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect::<Vec<_>>();
+    let params = wide(&params);
+
+    let res = unsafe {
+        ShellExecuteW(
+            ptr::null_mut(),
+            verb.as_ptr(),
+            exe.as_ptr(),
+            params.as_ptr(),
+            ptr::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+
+    // Anything up to 32 is an error code, e.g. when the UAC prompt is dismissed
+    if res as usize <= 32 {
+        bail!("Could not relaunch as administrator");
+    }
+
+    Ok(())
+}
+
+/// A NUL-terminated UTF-16 string for the Win32 API
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(Some(0)).collect()
+}
+
+/// The legacy path limit, counting the terminating NUL
+const MAX_PATH: usize = 260;
+
+/// Installs into a dedicated subfolder of the picked one, so uninstalling never wipes unrelated files
+pub fn install_dir_in(parent: PathBuf) -> Result<PathBuf> {
+    let dir = if parent.ends_with("TinyWiiBackupManager") {
+        parent
+    } else {
+        parent.join("TinyWiiBackupManager")
+    };
+
+    normalize_install_dir(&dir)
+}
+
+/// Makes `dir` absolute, without any \\?\ prefix or `..`, and checks it isn't too long to use
+fn normalize_install_dir(dir: &Path) -> Result<PathBuf> {
+    let dir = std::path::absolute(dir)?;
+    let dir = match dir.to_str() {
+        Some(dir) if dir.starts_with(r"\\?\UNC\") => PathBuf::from(format!(r"\\{}", &dir[8..])),
+        Some(dir) if dir.starts_with(r"\\?\") => PathBuf::from(&dir[4..]),
+        _ => dir,
+    };
+
+    check_install_dir_length(&dir)?;
+    Ok(dir)
+}
+
+/// std copes with long paths by itself, but shortcuts, the registry's command lines and the
+/// shell can't take \\?\ paths, so every file we put in the install dir has to fit in MAX_PATH
+fn check_install_dir_length(dir: &Path) -> Result<()> {
+    let longest_file = MANAGED_FILES
+        .iter()
+        .map(|file| backup_path(&dir.join(file)))
+        .map(|path| path.as_os_str().encode_wide().count())
+        .max()
+        .unwrap_or_default();
+
+    if longest_file >= MAX_PATH {
+        bail!(
+            "The install path is too long ({} characters), please pick a shorter one",
+            dir.as_os_str().encode_wide().count()
+        );
+    }
+
+    Ok(())
+}
+
+/// A NUL-terminated path for Win32, with the \\?\ prefix once it's too long to go without
+fn wide_path(path: &Path) -> Vec<u16> {
+    let mut wide = path.as_os_str().encode_wide().collect::<Vec<_>>();
+    let path_str = path.to_string_lossy();
+
+    if wide.len() >= MAX_PATH && path.is_absolute() && !path_str.starts_with(r"\\?\") {
+        // \\server\share becomes \\?\UNC\server\share
+        let prefix = if path_str.starts_with(r"\\") {
+            wide.remove(0);
+            r"\\?\UNC"
+        } else {
+            r"\\?\"
+        };
+        wide.splice(0..0, prefix.encode_utf16());
+    }
+
+    wide.push(0);
+    wide
+}
+
+/// The version recorded in the uninstall key, if there's a (sane) one
+pub fn installed_version() -> Result<Option<String>> {
+    let Some(key) = uninstall_key() else {
+        return Ok(None);
+    };
+
+    let version = match key.get_string("DisplayVersion") {
+        Ok(version) => version.trim().trim_start_matches('v').to_string(),
+        Err(_) => return Ok(None),
+    };
+
+    if version.is_empty() || !version.starts_with(|c: char| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+
+    Ok(Some(version))
+}
+
+/// Splits "v1.2.3-beta.1" into its numeric components and whether it's a pre-release
+fn parse_version(version: &str) -> Option<(Vec<u64>, bool)> {
+    let version = version.trim().trim_start_matches('v');
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, !pre.is_empty()),
+        None => (version, false),
+    };
+
+    let components = core
+        .split('.')
+        .map(|component| component.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+
+    Some((components, pre))
+}
+
+/// Compares versions numerically, so that 1.10.0 is newer than 1.9.0.
+/// Missing components count as 0, and malformed versions are never newer.
+pub fn is_newer(latest: &str, installed: &str) -> bool {
+    let (Some((latest, latest_pre)), Some((installed, installed_pre))) =
+        (parse_version(latest), parse_version(installed))
+    else {
+        return false;
+    };
+
+    let len = latest.len().max(installed.len());
+    for i in 0..len {
+        let a = latest.get(i).copied().unwrap_or_default();
+        let b = installed.get(i).copied().unwrap_or_default();
+        if a != b {
+            return a > b;
+        }
+    }
+
+    // Same numbers, a release is newer than its pre-releases
+    installed_pre && !latest_pre
+}
+
+/// Going by the exe, as the installer's own settings live in the default install dir
+pub fn is_installed() -> Result<bool> {
+    Ok(install_dir()?.join("TinyWiiBackupManager.exe").exists())
+}
+
+/// Seconds before giving up on a request, overridable with TWBM_INSTALLER_TIMEOUT
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+fn timeout_secs() -> u64 {
+    env::var("TWBM_INSTALLER_TIMEOUT")
+        .ok()
+        .and_then(|secs| secs.trim().parse().ok())
+        .filter(|&secs| secs > 0)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS)
+}
+
+/// Set from the network settings, takes precedence over the environment
+static PROXY_OVERRIDE: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn set_proxy_override(proxy: Option<String>) {
+    if let Ok(mut proxy_override) = PROXY_OVERRIDE.lock() {
+        *proxy_override = proxy.filter(|proxy| !proxy.trim().is_empty());
+    }
+}
+
+pub fn proxy_override() -> Option<String> {
+    PROXY_OVERRIDE.lock().ok().and_then(|proxy| proxy.clone())
+}
+
+/// The override if set, otherwise HTTPS_PROXY/HTTP_PROXY
+fn proxy() -> Option<String> {
+    proxy_override().or_else(|| {
+        ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|proxy| !proxy.trim().is_empty())
+    })
+}
+
+/// Set from --max-rate or the network settings, in KB/s. None downloads at full speed.
+static MAX_RATE: Mutex<Option<u64>> = Mutex::new(None);
+
+pub fn set_max_rate(kb_per_sec: Option<u64>) {
+    if let Ok(mut max_rate) = MAX_RATE.lock() {
+        *max_rate = kb_per_sec.filter(|&rate| rate > 0);
+    }
+}
+
+pub fn max_rate() -> Option<u64> {
+    MAX_RATE.lock().ok().and_then(|rate| *rate)
+}
+
+pub fn parse_max_rate(input: &str) -> Result<u64> {
+    match input.trim().parse::<u64>() {
+        Ok(rate) if rate > 0 => Ok(rate),
+        _ => bail!("The maximum rate must be a whole number of KB/s, like 500"),
+    }
+}
+
+/// Release assets redirect once to GitHub's CDN, a mirror may add a hop or two
+const MAX_REDIRECTS: usize = 5;
+
+/// The last URL we tried to fetch, for error reports
+static LAST_URL: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn last_url() -> Option<String> {
+    LAST_URL.lock().ok().and_then(|url| url.clone())
+}
+
+/// A GET request with a timeout, going through the configured proxy, if any
+fn get(url: &str) -> Result<minreq::Request, InstallError> {
+    request(minreq::Method::Get, url, Some(timeout_secs()))
+}
+
+/// minreq's timeout is a deadline for the whole request, body included, so downloads go
+/// without one and time out on their own when the connection goes idle
+fn request(
+    method: minreq::Method,
+    url: &str,
+    timeout_secs: Option<u64>,
+) -> Result<minreq::Request, InstallError> {
+    log::write(format!("{} {}", method, url));
+    if let Ok(mut last_url) = LAST_URL.lock() {
+        *last_url = Some(url.to_string());
+    }
+    let mut request = minreq::Request::new(method, url).with_max_redirects(MAX_REDIRECTS);
+    if let Some(timeout_secs) = timeout_secs {
+        request = request.with_timeout(timeout_secs);
+    }
+
+    match proxy() {
+        Some(proxy) => {
+            let parsed = minreq::Proxy::new(proxy.trim()).map_err(|e| {
+                InstallError::Network(format!("Invalid proxy \"{}\": {}", proxy, e))
+            })?;
+            Ok(request.with_proxy(parsed))
+        }
+        None => Ok(request),
+    }
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+    )
+}
+
+/// Blames the proxy, rather than the server, when we can't connect through it
+fn network_error(e: minreq::Error) -> InstallError {
+    if let minreq::Error::IoError(io_err) = &e
+        && is_timeout(io_err)
+    {
+        return InstallError::Network(format!(
+            "Network timed out after {} seconds",
+            timeout_secs()
+        ));
+    }
+
+    if matches!(
+        e,
+        minreq::Error::TooManyRedirections
+            | minreq::Error::InfiniteRedirectionLoop
+            | minreq::Error::RedirectLocationMissing
+    ) {
+        return InstallError::Network(format!("The server redirected too many times ({})", e));
+    }
+
+    match proxy() {
+        Some(proxy) if matches!(e, minreq::Error::ProxyConnect | minreq::Error::IoError(_)) => {
+            InstallError::Network(format!("Could not connect through proxy {}: {}", proxy, e))
+        }
+        _ => InstallError::Network(e.to_string()),
+    }
+}
+
+/// Where releases and version.txt are downloaded from. The GitHub API is not mirrored.
+const DEFAULT_BASE_URL: &str = "https://github.com/mq1/TinyWiiBackupManager";
+
+/// Set from --base-url, takes precedence over TWBM_BASE_URL
+static BASE_URL_OVERRIDE: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn set_base_url_override(url: Option<String>) {
+    if let Ok(mut base_url_override) = BASE_URL_OVERRIDE.lock() {
+        *base_url_override = url;
+    }
+}
+
+/// Checks a mirror's URL is well-formed. Plain http is allowed, the checksum still
+/// catches a tampered download, but it gets a warning.
+pub fn parse_base_url(url: &str) -> Result<String> {
+    let url = url.trim().trim_end_matches('/');
+    let host = match url.split_once("://") {
+        Some(("https", rest)) => rest,
+        Some(("http", rest)) => {
+            log::write(format!("Warning: the base URL {} is not https", url));
+            rest
+        }
+        _ => bail!("The base URL must start with https://, got \"{}\"", url),
+    };
+
+    if host.is_empty() || host.starts_with('/') || host.contains(char::is_whitespace) {
+        bail!("\"{}\" is not a valid base URL", url);
+    }
+
+    Ok(url.to_string())
+}
+
+/// The override if set, otherwise TWBM_BASE_URL, otherwise GitHub
+fn base_url() -> String {
+    if let Ok(base_url_override) = BASE_URL_OVERRIDE.lock()
+        && let Some(url) = base_url_override.clone()
+    {
+        return url;
+    }
+
+    match env::var("TWBM_BASE_URL") {
+        Ok(url) if !url.trim().is_empty() => parse_base_url(&url).unwrap_or_else(|e| {
+            log::write(format!("Ignoring TWBM_BASE_URL: {}", e));
+            DEFAULT_BASE_URL.to_string()
+        }),
+        _ => DEFAULT_BASE_URL.to_string(),
+    }
+}
+
+/// Set from --mirror, takes precedence over TWBM_MIRRORS
+static MIRRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub fn set_mirrors(mirrors: Vec<String>) {
+    if let Ok(mut current) = MIRRORS.lock() {
+        *current = mirrors;
+    }
+}
+
+/// Extra base URLs to race the main one against, none unless configured
+fn mirrors() -> Vec<String> {
+    if let Ok(mirrors) = MIRRORS.lock()
+        && !mirrors.is_empty()
+    {
+        return mirrors.clone();
+    }
+
+    // Comma or semicolon separated, like %PATH%
+    env::var("TWBM_MIRRORS")
+        .unwrap_or_default()
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .filter_map(|url| {
+            parse_base_url(url)
+                .inspect_err(|e| log::write(format!("Ignoring a TWBM_MIRRORS entry: {}", e)))
+                .ok()
+        })
+        .collect()
+}
+
+/// Where a release zip can come from, the base URL first
+fn download_sources() -> Vec<String> {
+    let mut sources = vec![base_url()];
+    for mirror in mirrors() {
+        if !sources.contains(&mirror) {
+            sources.push(mirror);
+        }
+    }
+
+    sources
+}
+
+/// The release zip `download` fetches, its checksum is this plus ".sha256"
+pub fn download_url(version: &str, os: Os, arch: Arch) -> String {
+    asset_url(&base_url(), version, &asset_name_for(version, os, arch))
+}
+
+fn asset_url(base_url: &str, version: &str, name: &str) -> String {
+    format!("{}/releases/download/v{}/{}", base_url, version, name)
+}
+
+/// What release zips were called over time, the current scheme first. Only that one for now:
+/// an older scheme goes after it once a release on the releases page is found under it, linked
+/// here, as each is another request on every miss.
+fn asset_name_candidates(version: &str, os: Os, arch: Arch) -> Vec<String> {
+    vec![format!(
+        "TinyWiiBackupManager-v{}-{}-{}.zip",
+        version,
+        os.as_str(),
+        arch.as_str()
+    )]
+}
+
+/// The name the release zip is downloaded under, the one its checksum and signature sit next to
+fn asset_name_for(version: &str, os: Os, arch: Arch) -> String {
+    asset_name_candidates(version, os, arch).swap_remove(0)
+}
+
+/// Normalizes a user-typed version like "v1.2.3", rejecting anything that isn't one
+pub fn parse_version_input(input: &str) -> Result<String> {
+    let version = input.trim().trim_start_matches('v');
+
+    if parse_version(version).is_none() {
+        bail!(
+            "\"{}\" is not a valid version (expected something like 1.2.3)",
+            input.trim()
+        );
+    }
+
+    Ok(version.to_string())
+}
+
+/// Checks with a HEAD request that the release has an asset for this os/arch
+pub async fn check_asset_exists(version: &str, os: Os, arch: Arch) -> Result<(), InstallError> {
+    for name in asset_name_candidates(version, os, arch) {
+        let url = asset_url(&base_url(), version, &name);
+        let response = request(minreq::Method::Head, &url, Some(timeout_secs()))?
+            .send()
+            .map_err(network_error)?;
+
+        match response.status_code {
+            200 => {
+                return Ok(());
+            }
+            404 => {}
+            status => {
+                return Err(InstallError::Network(format!(
+                    "Failed to check v{} (HTTP {})",
+                    version, status
+                )));
+            }
+        }
+    }
+
+    Err(InstallError::Other(format!(
+        "v{} has no release for {} {}",
+        version, os, arch
+    )))
+}
+
+/// Where an in-progress download is cached so a retry can resume it. Keyed on the whole
+/// URL, as every artifact's ends in /zip and resuming one from another would splice them.
+fn partial_download_path(url: &str) -> PathBuf {
+    let key = to_hex(&Sha256::digest(url.as_bytes()));
+    env::temp_dir().join(format!("{}-{}.part", asset_name(url), &key[..16]))
+}
+
+/// Where verified release zips are kept, so a reinstall or repair doesn't download them again
+#[cfg(not(test))]
+fn cache_dir() -> Result<PathBuf> {
+    let base_dirs = BaseDirs::new().ok_or(anyhow!("Failed to get base dirs"))?;
+
+    Ok(base_dirs
+        .cache_dir()
+        .join("TinyWiiBackupManagerInstaller")
+        .join("downloads"))
+}
+
+/// Tests get a cache per thread, rather than wiping the real one or each other's
+#[cfg(test)]
+fn cache_dir() -> Result<PathBuf> {
+    Ok(test_path("cache"))
+}
+
+/// A temp path of this test's own, named after the thread it runs on
+#[cfg(test)]
+fn test_path(name: &str) -> PathBuf {
+    let thread_id = format!("{:?}", thread::current().id());
+    let thread_id = thread_id
+        .trim_start_matches("ThreadId(")
+        .trim_end_matches(')');
+
+    env::temp_dir().join(format!(
+        "twbm-installer-test-{}-{}-{}",
+        name,
+        std::process::id(),
+        thread_id
+    ))
+}
+
+/// The cached zip for this release and the file with its checksum
+fn cache_paths(version: &str, os: Os, arch: Arch) -> Result<(PathBuf, PathBuf)> {
+    let zip_path = cache_dir()?.join(asset_name_for(version, os, arch));
+    let checksum_path = zip_path.with_extension("zip.sha256");
+
+    Ok((zip_path, checksum_path))
+}
+
+/// The cached zip, if there is one and it still matches the checksum it was verified against
+pub fn cached_download(version: &str, os: Os, arch: Arch) -> Option<Vec<u8>> {
+    let (zip_path, checksum_path) = cache_paths(version, os, arch).ok()?;
+    let checksum = fs::read_to_string(checksum_path).ok()?;
+    let bytes = fs::read(&zip_path).ok()?;
+
+    if !to_hex(&Sha256::digest(&bytes)).eq_ignore_ascii_case(checksum.trim()) {
+        log::write(format!(
+            "Ignoring corrupted cache entry {}",
+            zip_path.display()
+        ));
+        return None;
+    }
+
+    Some(bytes)
+}
+
+/// Caches a verified zip, evicting every other release so only the latest download is kept
+pub fn cache_download(version: &str, os: Os, arch: Arch, bytes: &[u8], hash: &str) -> Result<()> {
+    let (zip_path, checksum_path) = cache_paths(version, os, arch)?;
+    clear_cache()?;
+    fs::create_dir_all(cache_dir()?)?;
+
+    fs::write(&zip_path, bytes)?;
+    fs::write(&checksum_path, hash)?;
+    log::write(format!("Cached the download in {}", zip_path.display()));
+
+    Ok(())
+}
+
+pub fn has_cache() -> bool {
+    cache_dir()
+        .and_then(|dir| Ok(fs::read_dir(dir)?.next().is_some()))
+        .unwrap_or(false)
+}
+
+pub fn clear_cache() -> Result<()> {
+    let cache_dir = cache_dir()?;
+
+    match fs::remove_dir_all(&cache_dir) {
+        Ok(()) => {
+            log::write(format!("Cleared {}", cache_dir.display()));
+            Ok(())
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn asset_name(url: &str) -> &str {
+    url.rsplit('/').next().unwrap_or(url)
+}
+
+/// How often the download rate is sampled
+const RATE_SAMPLE: Duration = Duration::from_millis(250);
+
+/// How much a new sample counts in the moving average, the rest is history
+const RATE_SMOOTHING: f64 = 0.3;
+
+/// Below this many bytes per second the download counts as stalled
+const STALLED_RATE: f64 = 1024.0;
+
+/// How far along a download is
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Progress {
+    pub downloaded: u64,
+    /// None if the server didn't send a Content-Length
+    pub total: Option<u64>,
+    /// Smoothed bytes per second, None until the first sample
+    pub rate: Option<f64>,
+}
+
+impl Progress {
+    /// Seconds left at the current rate, None if there's no telling or it stalled
+    pub fn remaining_secs(&self) -> Option<u64> {
+        let total = self.total.filter(|&total| total > 0)?;
+        let rate = self.rate.filter(|&rate| rate >= STALLED_RATE)?;
+
+        Some((total.saturating_sub(self.downloaded) as f64 / rate).ceil() as u64)
+    }
+}
+
+/// What `installer::download` and `install` report as they go. The GUI turns these
+/// into messages, the command line into lines. Returning ControlFlow::Break cancels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProgressEvent {
+    BytesDownloaded(Progress),
+    /// Checking the checksum, and the signature if built with it
+    Verifying,
+    Extracting,
+    /// `done` of the `total` files are out of the archive, the exe first
+    ExtractedFile {
+        done: usize,
+        total: usize,
+    },
+    CreatingShortcuts,
+    WritingRegistry,
+}
+
+/// No release has ever been this small, the exe alone is several MiB
+const MIN_DOWNLOAD_SIZE: u64 = 100 * 1024;
+
+const DEFAULT_MAX_DOWNLOAD_MB: u64 = 500;
+
+fn max_download_mb() -> u64 {
+    env::var("TWBM_MAX_DOWNLOAD_MB")
+        .ok()
+        .and_then(|mb| mb.trim().parse().ok())
+        .filter(|&mb| mb > 0)
+        .unwrap_or(DEFAULT_MAX_DOWNLOAD_MB)
+}
+
+fn check_download_size(size: u64) -> Result<(), InstallError> {
+    let limit = max_download_mb();
+    if size < MIN_DOWNLOAD_SIZE {
+        Err(InstallError::DownloadTooSmall(size))
+    } else if size > limit * 1024 * 1024 {
+        Err(InstallError::DownloadTooLarge { size, limit })
+    } else {
+        Ok(())
+    }
+}
+
+pub async fn download(
+    version: String,
+    os: Os,
+    arch: Arch,
+    mut on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+) -> Result<(String, Vec<u8>, String), InstallError> {
+    let sources = download_sources();
+
+    // Older releases are only found under the names they were published with
+    let mut not_found = None;
+    for name in asset_name_candidates(&version, os, arch) {
+        let res = if sources.len() > 1 {
+            download_racing(&version, &name, &sources, &mut on_progress)
+        } else {
+            download_zip(
+                &asset_url(&sources[0], &version, &name),
+                None,
+                &mut on_progress,
+            )
+        };
+
+        match res {
+            Ok((bytes, hash)) => {
+                return Ok((version, bytes, hash));
+            }
+            Err(e @ InstallError::NotFound(_)) => {
+                log::write(format!("{} not found, trying the next name", name));
+                not_found.get_or_insert(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    // The current name is the one worth reporting
+    Err(not_found.unwrap_or(InstallError::NotFound(asset_name_for(&version, os, arch))))
+}
+
+/// Asks every source at once and downloads from the first one to answer, the others'
+/// connections are closed. The checksum still comes from the base URL alone.
+pub fn download_racing(
+    version: &str,
+    name: &str,
+    sources: &[String],
+    on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+) -> Result<(Vec<u8>, String), InstallError> {
+    // Every source has the same file name, so a partial download resumes from any of them
+    let urls = sources
+        .iter()
+        .map(|source| asset_url(source, version, name))
+        .collect::<Vec<_>>();
+    let partial_path = partial_download_path(&urls[0]);
+    let bytes = fs::read(&partial_path).unwrap_or_default();
+    if !bytes.is_empty() {
+        log::write(format!("Resuming download from byte {}", bytes.len()));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    for url in &urls {
+        let tx = tx.clone();
+        let url = url.clone();
+        let offset = bytes.len();
+        thread::spawn(move || {
+            let res = open_download(&url, None, offset);
+            // Fails once another source won, and dropping the response closes its connection
+            let _ = tx.send((url, res));
+        });
+    }
+    drop(tx);
+
+    let mut error = None;
+    let mut winner = None;
+    for (url, res) in &rx {
+        match res {
+            Ok(response) => {
+                winner = Some((url, response));
+                break;
+            }
+            Err(e) => {
+                log::write(format!("{} failed: {}", url, e));
+                // The base URL's error is the one to go by, e.g. a 404 for a missing v3 build
+                if url == urls[0] || error.is_none() {
+                    error = Some(e);
+                }
+            }
+        }
+    }
+    // Closes the connections that already answered, and those yet to as they do
+    drop(rx);
+
+    match winner {
+        Some((url, (response, offset))) => {
+            log::write(format!("Downloading from {}, the first to answer", url));
+            receive_download(response, bytes, offset, &partial_path, on_progress)
+        }
+        None => Err(error.unwrap_or(InstallError::Network(
+            "No download source answered".to_string(),
+        ))),
+    }
+}
+
+/// Falls back to GITHUB_TOKEN, as GitHub only hands out artifacts to signed in users
+const ARTIFACT_TOKEN_VAR: &str = "TWBM_ARTIFACT_TOKEN";
+
+/// Downloads a CI build rather than a release, with a version like "nightly-1a2b3c4"
+/// made up from the URL. There's no published checksum to verify it against.
+pub fn download_artifact(
+    url: &str,
+    on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+) -> Result<(String, Vec<u8>), InstallError> {
+    let api_url = artifact_api_url(url);
+    let token = [ARTIFACT_TOKEN_VAR, "GITHUB_TOKEN"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .map(|token| token.trim().to_string())
+        .find(|token| !token.is_empty());
+
+    log::write(format!("Downloading the artifact at {}", api_url));
+    let (bytes, _) = download_zip(&api_url, token.as_deref(), on_progress)?;
+
+    Ok((nightly_version(url), bytes))
+}
+
+/// The web page of an artifact (github.com/OWNER/REPO/actions/runs/RUN/artifacts/ID) only
+/// works in a browser, the API serves the same zip
+fn artifact_api_url(url: &str) -> String {
+    let url = url.trim();
+    let path = url
+        .strip_prefix("https://github.com/")
+        .map(|path| path.split(['?', '#']).next().unwrap_or(path));
+
+    match path
+        .map(|path| path.split('/').collect::<Vec<_>>())
+        .as_deref()
+    {
+        Some([owner, repo, "actions", "runs", _, "artifacts", id]) => format!(
+            "https://api.github.com/repos/{}/{}/actions/artifacts/{}/zip",
+            owner, repo, id
+        ),
+        _ => url.to_string(),
+    }
+}
+
+/// "nightly-" and the first commit hash in the URL, or else the artifact or run id
+fn nightly_version(url: &str) -> String {
+    let segments: Vec<&str> = url
+        .split(['/', '?', '&', '=', '#', '-', '_', '.'])
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let is_hash = |segment: &&str| {
+        (7..=40).contains(&segment.len())
+            && segment.chars().all(|c| c.is_ascii_hexdigit())
+            && !segment.chars().all(|c| c.is_ascii_digit())
+    };
+
+    match segments.iter().copied().find(is_hash) {
+        Some(hash) => format!("nightly-{}", &hash[..7].to_ascii_lowercase()),
+        None => match segments
+            .iter()
+            .rev()
+            .find(|segment| segment.chars().all(|c| c.is_ascii_digit()))
+        {
+            Some(id) => format!("nightly-{}", id),
+            None => "nightly".to_string(),
+        },
+    }
+}
+
+pub fn is_nightly(version: &str) -> bool {
+    version.starts_with("nightly")
+}
+
+/// "v1.2.3" for a release, "nightly-1a2b3c4 (nightly build)" for a CI one
+pub fn display_version(version: &str) -> String {
+    if is_nightly(version) {
+        format!("{} (nightly build)", version)
+    } else {
+        format!("v{}", version)
+    }
+}
+
+/// Streams the zip at `url`, resuming a partial download, and returns it with its SHA-256
+fn download_zip(
+    url: &str,
+    token: Option<&str>,
+    on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+) -> Result<(Vec<u8>, String), InstallError> {
+    let partial_path = partial_download_path(url);
+
+    // Pick up where a failed attempt left off
+    let bytes = fs::read(&partial_path).unwrap_or_default();
+    if !bytes.is_empty() {
+        log::write(format!("Resuming download from byte {}", bytes.len()));
+    }
+
+    let (response, offset) = open_download(url, token, bytes.len())?;
+    receive_download(response, bytes, offset, &partial_path, on_progress)
+}
+
+/// Sends the GET for a download, asking for what's past `offset` if that isn't 0.
+/// Returns the response and the offset it was finally asked from.
+fn open_download(
+    url: &str,
+    token: Option<&str>,
+    mut offset: usize,
+) -> Result<(minreq::ResponseLazy, usize), InstallError> {
+    let new_request = |url: &str| -> Result<minreq::Request, InstallError> {
+        let request = request(minreq::Method::Get, url, None)?;
+        Ok(match token {
+            Some(token) => request.with_header("Authorization", format!("Bearer {}", token)),
+            None => request,
+        })
+    };
+
+    let mut request = new_request(url)?;
+    if offset > 0 {
+        request = request.with_header("Range", format!("bytes={}-", offset));
+    }
+
+    let mut response = send_download(request)?;
+    if response.status_code == 416 {
+        // The cached part is stale (or already complete), so it can't be resumed
+        offset = 0;
+        response = send_download(new_request(url)?)?;
+    }
+    match response.status_code {
+        200 | 206 => {}
+        404 => return Err(InstallError::NotFound(asset_name(url).to_string())),
+        status @ (401 | 403) if url.contains("/actions/") => {
+            return Err(InstallError::Permission(format!(
+                "Downloading the artifact failed (HTTP {}), set {} to a token that can read it",
+                status, ARTIFACT_TOKEN_VAR
+            )));
+        }
+        status => {
+            return Err(InstallError::Network(format!(
+                "Download failed (HTTP {})",
+                status
+            )));
+        }
+    }
+
+    // A captive portal or a mirror's error page, rather than the archive
+    if response
+        .headers
+        .get("content-type")
+        .is_some_and(|content_type| content_type.starts_with("text/html"))
+    {
+        return Err(InstallError::Network(
+            "Got a web page instead of the download, check your connection".to_string(),
+        ));
+    }
+
+    Ok((response, offset))
+}
+
+/// How long a download may go without receiving anything, the headers included
+fn idle_timeout() -> Duration {
+    Duration::from_secs(timeout_secs())
+}
+
+/// Sends a request made without a timeout, giving up if the headers don't come within
+/// `idle_timeout`. A connection that never answers is left to the OS on its own thread.
+fn send_download(request: minreq::Request) -> Result<minreq::ResponseLazy, InstallError> {
+    let (tx, rx) = mpsc::sync_channel(1);
+    thread::spawn(move || {
+        let _ = tx.send(request.send_lazy());
+    });
+
+    match rx.recv_timeout(idle_timeout()) {
+        Ok(res) => res.map_err(network_error),
+        Err(_) => Err(InstallError::Network(format!(
+            "Network timed out after {} seconds",
+            timeout_secs()
+        ))),
+    }
+}
+
+/// Reads `reader` on its own thread in chunks of up to `chunk_len`, ending with an empty
+/// chunk or an error. Only a few chunks are buffered, so a throttled download isn't read ahead.
+fn read_chunks(
+    mut reader: impl Read + Send + 'static,
+    chunk_len: usize,
+) -> mpsc::Receiver<io::Result<Vec<u8>>> {
+    let (tx, rx) = mpsc::sync_channel(4);
+    thread::spawn(move || {
+        let mut buf = vec![0; chunk_len];
+        loop {
+            let res = reader.read(&mut buf).map(|n| buf[..n].to_vec());
+            let last = !matches!(&res, Ok(chunk) if !chunk.is_empty());
+            // Stops once the download is given up on, too
+            if tx.send(res).is_err() || last {
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+/// The next chunk from `read_chunks`, timing out when nothing came in for `idle_timeout`
+fn next_chunk(
+    chunks: &mpsc::Receiver<io::Result<Vec<u8>>>,
+    idle_timeout: Duration,
+) -> Result<Vec<u8>, InstallError> {
+    let timed_out = || InstallError::Network("Network timed out while downloading".to_string());
+
+    match chunks.recv_timeout(idle_timeout) {
+        Ok(Ok(chunk)) => Ok(chunk),
+        Ok(Err(e)) if is_timeout(&e) => Err(timed_out()),
+        Ok(Err(e)) => Err(e.into()),
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(timed_out()),
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(InstallError::Network(
+            "The download stopped unexpectedly".to_string(),
+        )),
+    }
+}
+
+/// Paces a download set to --max-rate by sleeping off whatever it gets ahead
+struct Throttle {
+    bytes_per_sec: u64,
+    started: Instant,
+    /// What was already downloaded when pacing started
+    start_len: usize,
+}
+
+impl Throttle {
+    fn new(bytes_per_sec: u64, start_len: usize) -> Self {
+        Self {
+            bytes_per_sec,
+            started: Instant::now(),
+            start_len,
+        }
+    }
+
+    /// About a tenth of a second's worth, so progress stays smooth
+    fn chunk_len(&self, buf_len: usize) -> usize {
+        (self.bytes_per_sec as usize / 10).clamp(1024, buf_len)
+    }
+
+    fn pace(&self, len: usize) {
+        let due =
+            Duration::from_secs_f64((len - self.start_len) as f64 / self.bytes_per_sec as f64);
+        if let Some(ahead) = due.checked_sub(self.started.elapsed()) {
+            thread::sleep(ahead);
+        }
+    }
+}
+
+/// Streams the body of an `open_download` response after the `offset` bytes already in
+/// `bytes`, and returns the whole zip with its SHA-256
+fn receive_download(
+    response: minreq::ResponseLazy,
+    mut bytes: Vec<u8>,
+    offset: usize,
+    partial_path: &Path,
+    mut on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+) -> Result<(Vec<u8>, String), InstallError> {
+    bytes.truncate(offset);
+    let resumed = response.status_code == 206
+        && response
+            .headers
+            .get("content-range")
+            .is_some_and(|range| range.starts_with(&format!("bytes {}-", bytes.len())));
+    if !resumed && !bytes.is_empty() {
+        // The server ignored the range, start over
+        log::write("Server does not support resuming, downloading from scratch");
+        bytes.clear();
+    }
+
+    let total = response
+        .headers
+        .get("content-length")
+        .and_then(|len| len.parse::<u64>().ok())
+        .map(|len| len + bytes.len() as u64);
+    if let Some(total) = total {
+        check_download_size(total)?;
+    }
+
+    // Hash as the bytes come in, so verifying is free once the download is done
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+
+    let mut partial = OpenOptions::new()
+        .create(true)
+        .append(resumed)
+        .write(true)
+        .truncate(!resumed)
+        .open(partial_path)?;
+
+    // Stream the body, reporting progress after every chunk
+    bytes.reserve(total.map_or(0, |total| total.saturating_sub(bytes.len() as u64)) as usize);
+    let mut rate = None;
+    let mut sample = (Instant::now(), bytes.len());
+
+    let throttle = max_rate().map(|rate| Throttle::new(rate * 1024, bytes.len()));
+    let chunk_len = throttle
+        .as_ref()
+        .map_or(64 * 1024, |throttle| throttle.chunk_len(64 * 1024));
+    let chunks = read_chunks(response, chunk_len);
+    loop {
+        let chunk = next_chunk(&chunks, idle_timeout())?;
+        if chunk.is_empty() {
+            break;
+        }
+
+        bytes.extend_from_slice(&chunk);
+        hasher.update(&chunk);
+        partial.write_all(&chunk)?;
+
+        // Without a Content-Length, this is the only thing stopping an endless stream
+        let limit = max_download_mb();
+        if bytes.len() as u64 > limit * 1024 * 1024 {
+            drop(partial);
+            let _ = fs::remove_file(partial_path);
+            return Err(InstallError::DownloadTooLarge {
+                size: bytes.len() as u64,
+                limit,
+            });
+        }
+
+        if let Some(throttle) = &throttle {
+            throttle.pace(bytes.len());
+        }
+
+        let elapsed = sample.0.elapsed();
+        if elapsed >= RATE_SAMPLE {
+            let current = (bytes.len() - sample.1) as f64 / elapsed.as_secs_f64();
+            rate = Some(rate.map_or(current, |rate| {
+                RATE_SMOOTHING * current + (1.0 - RATE_SMOOTHING) * rate
+            }));
+            sample = (Instant::now(), bytes.len());
+        }
+
+        let progress = Progress {
+            downloaded: bytes.len() as u64,
+            total,
+            rate,
+        };
+        if on_progress(progress).is_break() {
+            return Err(InstallError::Cancelled);
+        }
+    }
+
+    drop(partial);
+    let _ = fs::remove_file(partial_path);
+
+    log::write(format!("Downloaded {} bytes", bytes.len()));
+    check_download_size(bytes.len() as u64)?;
+    if !bytes.starts_with(b"PK\x03\x04") {
+        return Err(InstallError::NotAZip);
+    }
+
+    Ok((bytes, to_hex(&hasher.finalize())))
+}
+
+/// GETs the file with the `extension` next to the release zip, under whichever name
+/// the release has. None if it's under none of them.
+fn fetch_sidecar(
+    version: &str,
+    os: Os,
+    arch: Arch,
+    extension: &str,
+) -> Result<Option<minreq::Response>, InstallError> {
+    for name in asset_name_candidates(version, os, arch) {
+        let url = format!("{}.{}", asset_url(&base_url(), version, &name), extension);
+        let response = get(&url)?.send().map_err(network_error)?;
+        if response.status_code != 404 {
+            return Ok(Some(response));
+        }
+    }
+
+    Ok(None)
+}
+
+pub async fn fetch_checksum(version: &str, os: Os, arch: Arch) -> Result<String, InstallError> {
+    let Some(response) = fetch_sidecar(version, os, arch, "sha256")? else {
+        return Err(InstallError::NotFound(format!(
+            "{}.sha256",
+            asset_name_for(version, os, arch)
+        )));
+    };
+    if response.status_code != 200 {
+        return Err(InstallError::Network(format!(
+            "Failed to fetch checksum (HTTP {})",
+            response.status_code
+        )));
+    }
+
+    // The sidecar is in sha256sum format: "<hash>  <filename>"
+    let checksum = response
+        .as_str()?
+        .split_whitespace()
+        .next()
+        .ok_or(anyhow!("Empty checksum file"))?
+        .to_ascii_lowercase();
+
+    Ok(checksum)
+}
+
+/// The key releases are signed with
+#[cfg(feature = "signature")]
+const PUBLIC_KEY: &[u8] = include_bytes!("../assets/minisign.pub");
+
+/// Whether minisign.pub holds a key, rather than the placeholder's comment alone
+#[cfg(feature = "signature")]
+pub fn has_public_key() -> bool {
+    str::from_utf8(PUBLIC_KEY).is_ok_and(|key| {
+        key.lines()
+            .map(str::trim)
+            .any(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+    })
+}
+
+#[cfg(feature = "signature")]
+pub async fn fetch_signature(version: &str, os: Os, arch: Arch) -> Result<String, InstallError> {
+    // An unsigned release is no more trustworthy than a badly signed one
+    let Some(response) = fetch_sidecar(version, os, arch, "minisig")? else {
+        return Err(InstallError::Signature(format!(
+            "{}.minisig was not found on the server",
+            asset_name_for(version, os, arch)
+        )));
+    };
+    if response.status_code != 200 {
+        return Err(InstallError::Network(format!(
+            "Failed to fetch signature (HTTP {})",
+            response.status_code
+        )));
+    }
+
+    Ok(response.as_str()?.to_string())
+}
+
+/// Checks a release zip against its minisign signature and the embedded public key
+#[cfg(feature = "signature")]
+pub fn verify_signature(bytes: &[u8], sig: &str) -> Result<()> {
+    use minisign_verify::{PublicKey, Signature};
+
+    let public_key = PublicKey::decode(str::from_utf8(PUBLIC_KEY)?)?;
+    let signature = Signature::decode(sig)?;
+    public_key.verify(bytes, &signature, false)?;
+
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// SHA-256 of the installed TinyWiiBackupManager.exe, if there is one
+pub fn installed_exe_hash() -> Result<Option<String>> {
+    file_hash(&install_dir()?.join("TinyWiiBackupManager.exe"))
+}
+
+/// The hash of TinyWiiBackupManager.exe inside a release zip
+pub fn archived_exe_hash(bytes: Vec<u8>) -> Result<String, InstallError> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    let index = find_exe(&archive)?;
+    let mut archived_exe = archive.by_index(index)?;
+
+    let mut hasher = Sha256::new();
+    io::copy(&mut archived_exe, &mut hasher)?;
+
+    Ok(to_hex(&hasher.finalize()))
+}
+
+fn file_hash(path: &Path) -> Result<Option<String>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+
+    Ok(Some(to_hex(&hasher.finalize())))
+}
+
+/// Whether the installed exe is still the one we extracted. The published checksum
+/// is for the zip, so this compares against the hash recorded at install time.
+/// None if there's nothing to compare (older installs didn't record one).
+pub fn installed_exe_matches() -> Option<bool> {
+    let recorded = uninstall_key()?.get_string("ExeSha256").ok()?;
+    let actual = installed_exe_hash().ok()?;
+
+    Some(actual.is_some_and(|actual| actual.eq_ignore_ascii_case(&recorded)))
+}
+
+/// Compares the hash `download` computed against the published one
+pub fn verify_checksum(actual: &str, expected: &str) -> Result<(), InstallError> {
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(InstallError::Checksum {
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Writes the exe from a verified release zip to `dest_dir`, named after the version
+pub fn save_portable_exe(
+    version: &str,
+    bytes: Vec<u8>,
+    dest_dir: &Path,
+) -> Result<PathBuf, InstallError> {
+    let dest_path = dest_dir.join(format!("TinyWiiBackupManager-v{}-portable.exe", version));
+
+    let cursor = Cursor::new(bytes);
+    let mut archive = ZipArchive::new(cursor)?;
+
+    if dest_path.exists() {
+        fs::remove_file(&dest_path)?;
+    }
+
+    let index = find_exe(&archive)?;
+    let mut archived_exe = archive.by_index(index)?;
+    let mut file = File::create(&dest_path)?;
+    io::copy(&mut archived_exe, &mut file)?;
+
+    Ok(dest_path)
+}
+
+/// Parses the version out of a release asset name like "TinyWiiBackupManager-v1.2.3-windows-x86_64.zip"
+pub fn version_from_zip_name(path: &Path) -> Result<String> {
+    let rest = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix("TinyWiiBackupManager-v"))
+        .ok_or(anyhow!("Could not find a version in the file name"))?;
+
+    // The version can have dashes of its own, like 1.0.0-beta.1, so it ends at the
+    // first one followed by an os
+    let version = rest
+        .match_indices('-')
+        .map(|(i, _)| (&rest[..i], &rest[i + 1..]))
+        .find(|(_, tail)| Os::ALL.iter().any(|os| tail.starts_with(os.as_str())))
+        .map(|(version, _)| version)
+        .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+        .ok_or(anyhow!("Could not find a version in the file name"))?;
+
+    Ok(version.to_string())
+}
+
+#[derive(Deserialize)]
+struct Release {
+    #[serde(default)]
+    tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    body: Option<String>,
+}
+
+/// Whether a 403 (or 429) from the GitHub API is us running out of requests
+fn is_rate_limited(status_code: i32, headers: &HashMap<String, String>) -> bool {
+    matches!(status_code, 403 | 429)
+        && headers
+            .get("x-ratelimit-remaining")
+            .is_some_and(|remaining| remaining.trim() == "0")
+}
+
+/// Minutes until the rate limit resets, from the X-RateLimit-Reset epoch seconds
+fn rate_limit_reset_minutes(headers: &HashMap<String, String>) -> Option<u64> {
+    let reset = headers
+        .get("x-ratelimit-reset")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+
+    Some(reset.saturating_sub(now).div_ceil(60))
+}
+
+/// Tag names of the most recent releases, newest first, without the leading "v"
+pub async fn list_versions() -> Result<Vec<String>> {
+    let response =
+        github_get("https://api.github.com/repos/mq1/TinyWiiBackupManager/releases?per_page=20")?;
+
+    if response.status_code != 200 {
+        bail!("Failed to fetch releases (HTTP {})", response.status_code);
+    }
+
+    let versions = response
+        .json::<Vec<Release>>()?
+        .into_iter()
+        .filter(|release| !release.draft)
+        .map(|release| release.tag_name.trim_start_matches('v').to_string())
+        .collect();
+
+    Ok(versions)
+}
+
+/// Sends a GitHub API request, authenticated with GITHUB_TOKEN if it's set
+/// (unauthenticated requests are limited to 60 an hour)
+pub fn github_get(url: &str) -> Result<minreq::Response, InstallError> {
+    // The GitHub API rejects requests without a User-Agent
+    let mut request = get(url)?
+        .with_header("User-Agent", "TinyWiiBackupManagerInstaller")
+        .with_header("Accept", "application/vnd.github+json");
+
+    if let Ok(token) = env::var("GITHUB_TOKEN")
+        && !token.trim().is_empty()
+    {
+        request = request.with_header("Authorization", format!("Bearer {}", token.trim()));
+    }
+
+    let response = request.send().map_err(network_error)?;
+
+    if is_rate_limited(response.status_code, &response.headers) {
+        return Err(InstallError::RateLimited(rate_limit_reset_minutes(
+            &response.headers,
+        )));
+    }
+
+    Ok(response)
+}
+
+pub async fn get_release_notes(version: String) -> Result<String> {
+    let url = format!(
+        "https://api.github.com/repos/mq1/TinyWiiBackupManager/releases/tags/v{}",
+        version
+    );
+
+    let response = github_get(&url)?;
+
+    if response.status_code != 200 {
+        bail!(
+            "Failed to fetch release notes (HTTP {})",
+            response.status_code
+        );
+    }
+
+    let body = response.json::<Release>()?.body.unwrap_or_default();
+
+    Ok(simplify_markdown(&body))
+}
+
+/// Turns markdown into something readable as plain text
+fn simplify_markdown(markdown: &str) -> String {
+    markdown
+        .lines()
+        .map(|line| {
+            let line = line.trim_end();
+            let trimmed = line.trim_start();
+
+            if let Some(heading) = trimmed.strip_prefix('#') {
+                heading.trim_start_matches('#').trim().to_string()
+            } else if let Some(item) = trimmed
+                .strip_prefix("- ")
+                .or_else(|| trimmed.strip_prefix("* "))
+            {
+                let indent = &line[..line.len() - trimmed.len()];
+                format!("{}• {}", indent, item)
+            } else {
+                line.replace("**", "").replace('`', "")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// version.txt from the latest release, falling back to the API's latest tag
+/// if it's missing or doesn't hold a version
+pub async fn get_latest_version() -> Result<String> {
+    latest_version(
+        &base_url(),
+        "https://api.github.com/repos/mq1/TinyWiiBackupManager/releases/latest",
+    )
+}
+
+/// version.txt under `base_url`, or else the tag of the release at `latest_release_url`
+fn latest_version(base_url: &str, latest_release_url: &str) -> Result<String> {
+    let err = match fetch_version_file(base_url) {
+        Ok(version) => return Ok(version),
+        Err(err) => err,
+    };
+
+    log::write(format!(
+        "Could not read version.txt ({:#}), asking the releases API",
+        err
+    ));
+
+    match fetch_latest_tag(latest_release_url) {
+        Ok(version) => Ok(version),
+        Err(fallback_err) => {
+            log::write(format!(
+                "The releases API fallback failed too: {:#}",
+                fallback_err
+            ));
+            Err(err)
+        }
+    }
+}
+
+fn fetch_version_file(base_url: &str) -> Result<String> {
+    let url = format!("{}/releases/latest/download/version.txt", base_url);
+    let response = get(&url)?.send().map_err(network_error)?;
+
+    if response.status_code != 200 {
+        bail!(
+            "Failed to fetch version.txt (HTTP {})",
+            response.status_code
+        );
+    }
+
+    parse_version_file(response.as_str()?)
+}
+
+/// The tag of the release GitHub marks as latest, without the leading "v"
+fn fetch_latest_tag(latest_release_url: &str) -> Result<String> {
+    let response = github_get(latest_release_url)?;
+
+    if response.status_code != 200 {
+        bail!(
+            "Failed to fetch the latest release (HTTP {})",
+            response.status_code
+        );
+    }
+
+    parse_version_input(&response.json::<Release>()?.tag_name)
+}
+
+/// Cleans up version.txt, which may have a BOM or a CRLF if it was edited on Windows
+fn parse_version_file(body: &str) -> Result<String> {
+    let body = body.trim_start_matches('\u{feff}').trim();
+
+    // A captive portal or an error page rather than the file itself
+    if body.starts_with('<') {
+        bail!("Got a web page instead of a version number, check your connection");
+    }
+
+    parse_version_input(body)
+}
+
+pub async fn get_latest_version_for_channel(channel: Channel) -> Result<String> {
+    log::write(format!("Fetching the latest {} version", channel));
+
+    if channel == Channel::Stable {
+        return get_latest_version().await;
+    }
+
+    // Releases are listed newest first, pre-releases included
+    let response =
+        github_get("https://api.github.com/repos/mq1/TinyWiiBackupManager/releases?per_page=10")?;
+
+    if response.status_code != 200 {
+        bail!("Failed to fetch releases (HTTP {})", response.status_code);
+    }
+
+    let release = response
+        .json::<Vec<Release>>()?
+        .into_iter()
+        .find(|release| !release.draft)
+        .ok_or(anyhow!("No releases found"))?;
+
+    Ok(release.tag_name.trim_start_matches('v').to_string())
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Channel {
+    #[default]
+    Stable,
+    Prerelease,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 2] = [Channel::Stable, Channel::Prerelease];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Prerelease => "prerelease",
+        }
+    }
+
+    pub fn as_display_str(&self) -> &'static str {
+        match self {
+            Channel::Stable => "Stable",
+            Channel::Prerelease => "Pre-release",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_display_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThemePreference {
+    #[default]
+    System,
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl ThemePreference {
+    pub const ALL: [ThemePreference; 4] = [
+        ThemePreference::System,
+        ThemePreference::Light,
+        ThemePreference::Dark,
+        ThemePreference::HighContrast,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThemePreference::System => "system",
+            ThemePreference::Light => "light",
+            ThemePreference::Dark => "dark",
+            ThemePreference::HighContrast => "high-contrast",
+        }
+    }
+
+    pub fn as_display_str(&self) -> &'static str {
+        match self {
+            ThemePreference::System => "System",
+            ThemePreference::Light => "Light",
+            ThemePreference::Dark => "Dark",
+            ThemePreference::HighContrast => "High contrast",
+        }
+    }
+
+    /// Whether to go light, asking Windows for System
+    pub fn is_light(&self) -> bool {
+        match self {
+            ThemePreference::System => system_uses_light_theme(),
+            ThemePreference::Light => true,
+            ThemePreference::Dark | ThemePreference::HighContrast => false,
+        }
+    }
+
+    /// Whether to use the high-contrast styling, following Windows for System
+    pub fn is_high_contrast(&self) -> bool {
+        match self {
+            ThemePreference::System => system_uses_high_contrast(),
+            ThemePreference::HighContrast => true,
+            ThemePreference::Light | ThemePreference::Dark => false,
+        }
+    }
+}
+
+impl fmt::Display for ThemePreference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_display_str())
+    }
+}
+
+/// Windows apps are light unless AppsUseLightTheme says otherwise
+fn system_uses_light_theme() -> bool {
+    CURRENT_USER
+        .open(PERSONALIZE_KEY)
+        .and_then(|key| key.get_u32("AppsUseLightTheme"))
+        .ok()
+        != Some(0)
+}
+
+/// Whether the Windows high-contrast mode is on
+fn system_uses_high_contrast() -> bool {
+    let mut high_contrast = HIGHCONTRASTW {
+        cbSize: size_of::<HIGHCONTRASTW>() as u32,
+        ..Default::default()
+    };
+
+    let ok = unsafe {
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            high_contrast.cbSize,
+            (&raw mut high_contrast).cast(),
+            0,
+        )
+    };
+
+    ok != 0 && high_contrast.dwFlags & HCF_HIGHCONTRASTON != 0
+}
+
+/// What the installer remembers between runs
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Prefs {
+    pub channel: Channel,
+    /// Only set once the user picked one
+    pub install_dir: Option<PathBuf>,
+    pub theme: ThemePreference,
+    /// Only set once the user picked one, otherwise the Windows display language is used
+    pub lang: Option<Lang>,
+    pub options: InstallOptions,
+    /// Where the window was last, in logical pixels. Only set once it was moved.
+    pub window_position: Option<(i32, i32)>,
+    pub window_size: Option<(u32, u32)>,
+    /// The update the user dismissed, until a newer one comes out
+    pub skipped_version: Option<String>,
+    /// Go straight to the install screen, without the welcome one
+    pub skip_welcome: bool,
+}
+
+/// Where the installer keeps its own settings
+#[cfg(not(test))]
+fn prefs_path() -> Result<PathBuf> {
+    let base_dirs = BaseDirs::new().ok_or(anyhow!("Failed to get base dirs"))?;
+
+    Ok(base_dirs
+        .data_local_dir()
+        .join("TinyWiiBackupManager")
+        .join("installer.toml"))
+}
+
+#[cfg(test)]
+fn prefs_path() -> Result<PathBuf> {
+    Ok(test_path("prefs").join("installer.toml"))
+}
+
+/// The saved preferences, with defaults for anything missing or unreadable
+pub fn load_prefs() -> Prefs {
+    let Ok(text) = prefs_path().and_then(|path| Ok(fs::read_to_string(path)?)) else {
+        return Prefs::default();
+    };
+    let prefs = match text.parse::<DocumentMut>() {
+        Ok(prefs) => prefs,
+        Err(e) => {
+            log::write(format!("Ignoring the unreadable preferences: {}", e));
+            return Prefs::default();
+        }
+    };
+
+    let string = |name| prefs.get(name).and_then(Item::as_str).unwrap_or_default();
+    let flag = |name| prefs.get(name).and_then(Item::as_bool).unwrap_or(false);
+    // For the ones that are on unless turned off
+    let flag_or_on = |name| prefs.get(name).and_then(Item::as_bool).unwrap_or(true);
+    let number = |name| prefs.get(name).and_then(Item::as_integer);
+
+    Prefs {
+        channel: Channel::ALL
+            .into_iter()
+            .find(|channel| channel.as_str() == string("channel"))
+            .unwrap_or_default(),
+        install_dir: Some(string("install_dir"))
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from),
+        theme: ThemePreference::ALL
+            .into_iter()
+            .find(|theme| theme.as_str() == string("theme"))
+            .unwrap_or_default(),
+        lang: Lang::ALL
+            .into_iter()
+            .find(|lang| lang.as_str() == string("language")),
+        options: InstallOptions {
+            desktop_shortcut: flag_or_on("desktop_shortcut"),
+            start_menu_shortcut: flag_or_on("start_menu_shortcut"),
+            pin_to_taskbar: flag("pin_to_taskbar"),
+            associate_files: flag("associate_files"),
+            associate_iso: flag("associate_iso"),
+            add_to_path: flag("add_to_path"),
+            create_restore_point: flag("create_restore_point"),
+            run_on_startup: flag("run_on_startup"),
+            scope: Scope::ALL
+                .into_iter()
+                .find(|scope| scope.as_str() == string("scope"))
+                .unwrap_or_default(),
+            portable: false,
+        },
+        window_position: number("window_x")
+            .zip(number("window_y"))
+            .and_then(|(x, y)| Some((x.try_into().ok()?, y.try_into().ok()?))),
+        window_size: number("window_width")
+            .zip(number("window_height"))
+            .and_then(|(width, height)| Some((width.try_into().ok()?, height.try_into().ok()?))),
+        skipped_version: Some(string("skipped_version"))
+            .filter(|version| !version.is_empty())
+            .map(str::to_string),
+        skip_welcome: flag("skip_welcome"),
+    }
+}
+
+pub fn save_prefs(prefs: &Prefs) -> Result<()> {
+    let mut doc = DocumentMut::new();
+
+    doc["channel"] = value(prefs.channel.as_str());
+    if let Some(dir) = &prefs.install_dir {
+        doc["install_dir"] = value(dir.to_string_lossy().into_owned());
+    }
+    doc["theme"] = value(prefs.theme.as_str());
+    if let Some(lang) = prefs.lang {
+        doc["language"] = value(lang.as_str());
+    }
+    doc["desktop_shortcut"] = value(prefs.options.desktop_shortcut);
+    doc["start_menu_shortcut"] = value(prefs.options.start_menu_shortcut);
+    doc["pin_to_taskbar"] = value(prefs.options.pin_to_taskbar);
+    doc["associate_files"] = value(prefs.options.associate_files);
+    doc["associate_iso"] = value(prefs.options.associate_iso);
+    doc["add_to_path"] = value(prefs.options.add_to_path);
+    doc["create_restore_point"] = value(prefs.options.create_restore_point);
+    doc["run_on_startup"] = value(prefs.options.run_on_startup);
+    doc["scope"] = value(prefs.options.scope.as_str());
+    if let Some((x, y)) = prefs.window_position {
+        doc["window_x"] = value(i64::from(x));
+        doc["window_y"] = value(i64::from(y));
+    }
+    if let Some((width, height)) = prefs.window_size {
+        doc["window_width"] = value(i64::from(width));
+        doc["window_height"] = value(i64::from(height));
+    }
+    doc["skip_welcome"] = value(prefs.skip_welcome);
+    if let Some(version) = &prefs.skipped_version {
+        doc["skipped_version"] = value(version);
+    }
+
+    let path = prefs_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    // Written aside and moved over, so a crash halfway can't leave half a file
+    let temp_path = path.with_extension("toml.tmp");
+    fs::write(&temp_path, doc.to_string())?;
+    fs::rename(&temp_path, &path)?;
+
+    Ok(())
+}
+
+/// Whether a remembered window position is still on one of the monitors,
+/// which it may not be once one was unplugged
+pub fn is_on_screen(x: i32, y: i32) -> bool {
+    let (left, top, width, height) = unsafe {
+        (
+            GetSystemMetrics(SM_XVIRTUALSCREEN),
+            GetSystemMetrics(SM_YVIRTUALSCREEN),
+            GetSystemMetrics(SM_CXVIRTUALSCREEN),
+            GetSystemMetrics(SM_CYVIRTUALSCREEN),
+        )
+    };
+
+    (left..left + width).contains(&x) && (top..top + height).contains(&y)
+}
+
+/// Changes one preference and saves, which isn't worth bothering the user about if it fails
+pub fn update_prefs(update: impl FnOnce(&mut Prefs)) {
+    let mut prefs = load_prefs();
+    update(&mut prefs);
+
+    if let Err(e) = save_prefs(&prefs) {
+        log::write(format!("Failed to save preferences: {}", e));
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Os {
+    Windows11,
+    #[default]
+    Windows,
+    Windows7,
+}
+
+impl Os {
+    pub const ALL: [Os; 3] = [Os::Windows11, Os::Windows, Os::Windows7];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Os::Windows11 | Os::Windows => "windows",
+            Os::Windows7 => "windows7",
+        }
+    }
+
+    pub fn as_display_str(&self) -> &'static str {
+        match self {
+            Os::Windows11 => "Windows 11",
+            Os::Windows => "Windows 10",
+            Os::Windows7 => "Windows 7/8/8.1",
+        }
+    }
+}
+
+impl fmt::Display for Os {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_display_str())
+    }
+}
+
+pub fn get_build_number() -> Result<u32> {
+    let key = LOCAL_MACHINE.open(CURRENT_VERSION_KEY)?;
+    let build_number = key.get_string("CurrentBuildNumber")?.trim().parse()?;
+
+    Ok(build_number)
+}
+
+/// Windows 11 still reports "Windows 10" as its ProductName, so go by build number
+pub fn os_from_build_number(build_number: u32) -> Os {
+    match build_number {
+        22000.. => Os::Windows11,
+        10240.. => Os::Windows,
+        _ => Os::Windows7,
+    }
+}
+
+/// Older than Windows 7
+#[derive(Debug, thiserror::Error)]
+#[error("This installer requires Windows 7 or later")]
+pub struct UnsupportedOs;
+
+/// Windows 7 RTM
+const MIN_BUILD_NUMBER: u32 = 7600;
+
+pub fn get_os() -> Result<Os, UnsupportedOs> {
+    if let Ok(build_number) = get_build_number() {
+        return if build_number >= MIN_BUILD_NUMBER {
+            Ok(os_from_build_number(build_number))
+        } else {
+            Err(UnsupportedOs)
+        };
+    }
+
+    os_from_registry(|| {
+        LOCAL_MACHINE
+            .open(CURRENT_VERSION_KEY)?
+            .get_string("ProductName")
+    })
+}
+
+/// Takes ProductName from `read_product_name`, so the missing value case can be tested
+fn os_from_registry(
+    read_product_name: impl FnOnce() -> windows_result::Result<String>,
+) -> Result<Os, UnsupportedOs> {
+    // Customized and Server installs may lack these values entirely, so assume the common case
+    match read_product_name() {
+        Ok(product_name) => os_from_product_name(&product_name),
+        Err(e) => {
+            log::write(format!(
+                "Could not read the Windows version, assuming Windows 10: {}",
+                e
+            ));
+            Ok(Os::Windows)
+        }
+    }
+}
+
+/// Anything that isn't recognizably older goes by the most common case
+fn os_from_product_name(product_name: &str) -> Result<Os, UnsupportedOs> {
+    if product_name.contains("Windows XP") || product_name.contains("Windows Vista") {
+        Err(UnsupportedOs)
+    } else if product_name.contains("Windows 7") || product_name.contains("Windows 8") {
+        Ok(Os::Windows7)
+    } else {
+        Ok(Os::Windows)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arch {
+    I686,
+    X86_64,
+    X86_64v3,
+    Aarch64,
+}
+
+impl Arch {
+    pub const ALL: [Arch; 4] = [Arch::I686, Arch::X86_64, Arch::X86_64v3, Arch::Aarch64];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Arch::I686 => "x86",
+            Arch::X86_64 => "x86_64",
+            Arch::X86_64v3 => "x86_64-v3",
+            Arch::Aarch64 => "arm64",
+        }
+    }
+
+    /// Whether a build for this arch runs on the `detected` processor.
+    /// ARM64 Windows emulates x86 and x86_64, but not the AVX2 the v3 builds need.
+    pub fn is_compatible_with(&self, detected: Arch) -> bool {
+        match detected {
+            Arch::I686 => *self == Arch::I686,
+            Arch::X86_64 => matches!(self, Arch::I686 | Arch::X86_64),
+            Arch::X86_64v3 => matches!(self, Arch::I686 | Arch::X86_64 | Arch::X86_64v3),
+            Arch::Aarch64 => matches!(self, Arch::I686 | Arch::X86_64 | Arch::Aarch64),
+        }
+    }
+
+    pub fn as_display_str(&self) -> &'static str {
+        match self {
+            Arch::I686 => "x86 (32-bit)",
+            Arch::X86_64 => "x86 (64-bit)",
+            Arch::X86_64v3 => "x86 (64-bit with AVX2 instructions)",
+            Arch::Aarch64 => "ARM64",
+        }
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_display_str())
+    }
+}
+
+/// Creates a restore point named `description`, doing nothing if System Restore is turned off.
+/// SRSetRestorePointW is looked up at runtime as Windows Server doesn't have srclient.dll.
+pub fn create_restore_point(description: &str) -> Result<()> {
+    let srclient = wide("srclient.dll");
+    let module = unsafe { LoadLibraryW(srclient.as_ptr()) };
+    if module.is_null() {
+        log::write("System Restore isn't available, skipping the restore point");
+        return Ok(());
+    }
+
+    let res = (|| {
+        let sr_set_restore_point =
+            unsafe { GetProcAddress(module, c"SRSetRestorePointW".as_ptr().cast()) }
+                .ok_or(anyhow!("srclient.dll has no SRSetRestorePointW"))?;
+        type SRSetRestorePointW =
+            unsafe extern "system" fn(*const RESTOREPOINTINFOW, *mut STATEMGRSTATUS) -> i32;
+        let sr_set_restore_point: SRSetRestorePointW =
+            unsafe { std::mem::transmute(sr_set_restore_point) };
+
+        // Leaves room for the terminating NUL
+        let mut description_w = [0; MAX_DESC_W as usize];
+        for (dst, src) in description_w
+            .iter_mut()
+            .take(MAX_DESC_W as usize - 1)
+            .zip(description.encode_utf16())
+        {
+            *dst = src;
+        }
+        let mut info = RESTOREPOINTINFOW {
+            dwEventType: BEGIN_SYSTEM_CHANGE,
+            dwRestorePtType: APPLICATION_INSTALL,
+            llSequenceNumber: 0,
+            szDescription: description_w,
+        };
+
+        let mut status = STATEMGRSTATUS::default();
+        if unsafe { sr_set_restore_point(&info, &mut status) } == 0 {
+            if status.nStatus == ERROR_SERVICE_DISABLED {
+                log::write("System Restore is turned off, skipping the restore point");
+                return Ok(());
+            }
+            bail!(io::Error::from_raw_os_error(status.nStatus as i32));
+        }
+
+        // The restore point is what we're after, so there's no change to wait for
+        info.dwEventType = END_SYSTEM_CHANGE;
+        let sequence_number = status.llSequenceNumber;
+        info.llSequenceNumber = sequence_number;
+        unsafe { sr_set_restore_point(&info, &mut status) };
+
+        log::write(format!(
+            "Created restore point {} \"{}\"",
+            sequence_number, description
+        ));
+        Ok(())
+    })();
+
+    unsafe { FreeLibrary(module) };
+    res
+}
+
+/// The machine's native arch as an IMAGE_FILE_MACHINE_* value, even under emulation.
+/// IsWow64Process2 is looked up at runtime as Windows 7 doesn't have it.
+fn native_machine() -> Option<IMAGE_FILE_MACHINE> {
+    let kernel32 = wide("kernel32.dll");
+    let is_wow64_process2 = unsafe {
+        GetProcAddress(
+            GetModuleHandleW(kernel32.as_ptr()),
+            c"IsWow64Process2".as_ptr().cast(),
+        )
+    };
+
+    if let Some(is_wow64_process2) = is_wow64_process2 {
+        type IsWow64Process2 = unsafe extern "system" fn(
+            *mut std::ffi::c_void,
+            *mut IMAGE_FILE_MACHINE,
+            *mut IMAGE_FILE_MACHINE,
+        ) -> i32;
+        let is_wow64_process2: IsWow64Process2 = unsafe { std::mem::transmute(is_wow64_process2) };
+
+        let mut process_machine = 0;
+        let mut native_machine = 0;
+        let ok = unsafe {
+            is_wow64_process2(
+                GetCurrentProcess(),
+                &mut process_machine,
+                &mut native_machine,
+            )
+        };
+        if ok != 0 {
+            return Some(native_machine);
+        }
+    }
+
+    // Reports the emulated arch under ARM64 emulation, but it's what older systems have
+    let mut info = SYSTEM_INFO::default();
+    unsafe { GetNativeSystemInfo(&mut info) };
+    match unsafe { info.Anonymous.Anonymous.wProcessorArchitecture } {
+        PROCESSOR_ARCHITECTURE_AMD64 => Some(IMAGE_FILE_MACHINE_AMD64),
+        PROCESSOR_ARCHITECTURE_ARM64 => Some(IMAGE_FILE_MACHINE_ARM64),
+        PROCESSOR_ARCHITECTURE_INTEL => Some(IMAGE_FILE_MACHINE_I386),
+        _ => None,
+    }
+}
+
+/// Picks the base arch from the native machine, falling back to PROCESSOR_ARCHITEW6432
+/// (only set for WOW64 processes) and then PROCESSOR_ARCHITECTURE
+fn arch_from(
+    native_machine: Option<IMAGE_FILE_MACHINE>,
+    processor_architew6432: Option<&str>,
+    processor_architecture: Option<&str>,
+) -> Arch {
+    match native_machine {
+        Some(IMAGE_FILE_MACHINE_AMD64) => return Arch::X86_64,
+        Some(IMAGE_FILE_MACHINE_ARM64) => return Arch::Aarch64,
+        Some(IMAGE_FILE_MACHINE_I386) => return Arch::I686,
+        _ => {}
+    }
+
+    match processor_architew6432.or(processor_architecture) {
+        Some(arch) if arch.eq_ignore_ascii_case("AMD64") => Arch::X86_64,
+        Some(arch) if arch.eq_ignore_ascii_case("ARM64") => Arch::Aarch64,
+        _ => Arch::I686,
+    }
+}
+
+pub fn get_arch() -> Arch {
+    let processor_architew6432 = env::var("PROCESSOR_ARCHITEW6432").ok();
+    let processor_architecture = env::var("PROCESSOR_ARCHITECTURE").ok();
+
+    match arch_from(
+        native_machine(),
+        processor_architew6432.as_deref(),
+        processor_architecture.as_deref(),
+    ) {
+        Arch::X86_64
+            if std::is_x86_feature_detected!("avx2")
+                && std::is_x86_feature_detected!("fma")
+                && std::is_x86_feature_detected!("bmi2") =>
+        {
+            Arch::X86_64v3
+        }
+        arch => arch,
+    }
+}
+
+pub fn launch_twbm() -> Result<()> {
+    let install_dir = install_dir()?;
+    let exe_path = install_dir.join("TinyWiiBackupManager.exe");
+    let exe_path_str = exe_path.to_str().ok_or(anyhow!("Failed to get exe path"))?;
+
+    Command::new("cmd")
+        .args(["/C", "start", "/B", exe_path_str])
+        .current_dir(install_dir)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW (run invisibly)
+        .spawn()?;
+
+    Ok(())
+}
+
+pub fn launch_twbm_portable(exe_path: PathBuf) -> Result<()> {
+    let parent = exe_path.parent().ok_or(anyhow!("Failed to get parent"))?;
+    let exe_path_str = exe_path.to_str().ok_or(anyhow!("Failed to get exe path"))?;
+
+    Command::new("cmd")
+        .args(["/C", "start", "/B", exe_path_str])
+        .current_dir(parent)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW (run invisibly)
+        .spawn()?;
+
+    Ok(())
+}
+
+/// Opens `url` in the default browser
+pub fn open_url(url: &str) -> Result<()> {
+    Command::new("explorer")
+        .arg(url)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW (run invisibly)
+        .spawn()?;
+
+    Ok(())
+}
+
+const NEW_ISSUE_URL: &str = "https://github.com/mq1/TinyWiiBackupManagerInstaller/issues/new";
+
+/// A new-issue page on the installer's repo with the title and body already filled in
+pub fn new_issue_url(title: &str, body: &str) -> String {
+    format!(
+        "{}?title={}&body={}",
+        NEW_ISSUE_URL,
+        url_encode(title),
+        url_encode(body)
+    )
+}
+
+/// Percent-encodes everything but the characters a query value can hold as they are
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Opens `dir` in Explorer, which would silently open Documents instead if it's gone
+pub fn open_folder(dir: &Path) -> Result<()> {
+    if !dir.is_dir() {
+        bail!("{} no longer exists", dir.display());
+    }
+
+    Command::new("explorer")
+        .arg(dir)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW (run invisibly)
+        .spawn()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    /// A fresh, empty folder under %TEMP% for the test called `name`
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "twbm-installer-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    fn zip_of(entries: &[(&str, &[u8])]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    /// Enough of a DOS header and PE signature to pass for an exe
+    fn fake_exe() -> Vec<u8> {
+        let mut exe = b"MZ".to_vec();
+        exe.resize(512, 0);
+        exe[60..64].copy_from_slice(&0x80u32.to_le_bytes());
+        exe[0x80..0x84].copy_from_slice(b"PE\0\0");
+        exe
+    }
+
+    /// A release with the exe in a folder, like the real ones, and a bundled file next to it
+    fn release_zip() -> ZipArchive<Cursor<Vec<u8>>> {
+        zip_of(&[
+            ("TinyWiiBackupManager/TinyWiiBackupManager.exe", &fake_exe()),
+            ("TinyWiiBackupManager/lib/readme.txt", b"readme"),
+        ])
+    }
+
+    /// An install dir holding the exe and uninstaller an update would replace
+    fn previous_install(name: &str) -> PathBuf {
+        let dir = temp_dir(name);
+        fs::write(dir.join("TinyWiiBackupManager.exe"), b"old exe").unwrap();
+        fs::write(dir.join("uninstall.exe"), b"old uninstaller").unwrap();
+
+        dir
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+
+        names
+    }
+
+    fn assert_previous_install(dir: &Path) {
+        assert_eq!(
+            fs::read(dir.join("TinyWiiBackupManager.exe")).unwrap(),
+            b"old exe"
+        );
+        assert_eq!(
+            fs::read(dir.join("uninstall.exe")).unwrap(),
+            b"old uninstaller"
+        );
+        assert_eq!(
+            file_names(dir),
+            ["TinyWiiBackupManager.exe", "uninstall.exe"]
+        );
+    }
+
+    /// Runs the steps like `install` does and rolls back at the first one to fail,
+    /// returning its error
+    fn run_until_failure(
+        actions: &[InstallAction],
+        archive: &mut ZipArchive<Cursor<Vec<u8>>>,
+        dir: &Path,
+        on_progress: &mut dyn FnMut(ProgressEvent) -> ControlFlow<()>,
+    ) -> InstallError {
+        let mut undos = Vec::new();
+        for action in actions {
+            match run_action(action, archive, "1.0.0", dir, on_progress) {
+                Ok(undo) => undos.extend(undo),
+                Err(error) => {
+                    assert_eq!(roll_back(undos), Vec::<String>::new());
+                    return error;
+                }
+            }
+        }
+
+        panic!("no step failed");
+    }
+
+    #[test]
+    fn finds_the_exe_at_the_root() {
+        let archive = zip_of(&[
+            ("readme.txt", b"readme"),
+            ("TinyWiiBackupManager.exe", &fake_exe()),
+        ]);
+        assert_eq!(find_exe(&archive).unwrap(), 1);
+    }
+
+    #[test]
+    fn finds_the_exe_in_a_subfolder() {
+        let archive = zip_of(&[
+            ("TinyWiiBackupManager-v1.2.3/readme.txt", b"readme"),
+            (
+                "TinyWiiBackupManager-v1.2.3/bin\\TinyWiiBackupManager.exe",
+                &fake_exe(),
+            ),
+        ]);
+        assert_eq!(find_exe(&archive).unwrap(), 1);
+    }
+
+    #[test]
+    fn no_exe_in_the_archive() {
+        let archive = zip_of(&[
+            ("readme.txt", b"readme"),
+            ("NotTinyWiiBackupManager.exe", &fake_exe()),
+        ]);
+        assert!(matches!(find_exe(&archive), Err(InstallError::Unzip(_))));
+    }
+
+    #[test]
+    fn safe_relative_path_refuses_escapes() {
+        for name in [
+            "..\\evil.txt",
+            "../evil.txt",
+            "lib/../../evil.txt",
+            "/etc/evil.txt",
+            "\\Windows\\evil.txt",
+            "C:\\Windows\\evil.txt",
+            "C:evil.txt",
+            "lib/C:/evil.txt",
+            "",
+            "./",
+        ] {
+            assert_eq!(safe_relative_path(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn safe_relative_path_keeps_nested_files() {
+        assert_eq!(
+            safe_relative_path("lib/readme.txt"),
+            Some(Path::new("lib").join("readme.txt"))
+        );
+        assert_eq!(
+            safe_relative_path(".\\lib\\readme.txt"),
+            Some(Path::new("lib").join("readme.txt"))
+        );
+        assert_eq!(
+            safe_relative_path("readme..txt"),
+            Some(PathBuf::from("readme..txt"))
+        );
+    }
+
+    #[test]
+    fn zip_slip_entry_fails_before_anything_is_written() {
+        let archive = zip_of(&[
+            ("TinyWiiBackupManager.exe", &fake_exe()),
+            ("..\\evil.txt", b"evil"),
+        ]);
+
+        assert!(matches!(
+            bundled_entries(&archive),
+            Err(InstallError::Unzip(_))
+        ));
+    }
+
+    #[test]
+    fn extracts_bundled_files_next_to_the_exe() {
+        let dir = temp_dir("extract-bundled");
+        let mut archive = zip_of(&[
+            ("TinyWiiBackupManager/TinyWiiBackupManager.exe", &fake_exe()),
+            ("TinyWiiBackupManager/readme.txt", b"readme"),
+            ("TinyWiiBackupManager/lib/data.bin", b"data"),
+            // Ours, never taken from the archive
+            ("TinyWiiBackupManager/uninstall.exe", b"not ours"),
+            // Not next to the exe
+            ("other/skipped.txt", b"skipped"),
+        ]);
+
+        let entries = bundled_entries(&archive).unwrap();
+        let mut written = Vec::new();
+        let mut done = Vec::new();
+        extract_bundled_files(&mut archive, &entries, &dir, &mut written, &mut |n| {
+            done.push(n);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+
+        assert_eq!(fs::read(dir.join("readme.txt")).unwrap(), b"readme");
+        assert_eq!(fs::read(dir.join("lib").join("data.bin")).unwrap(), b"data");
+        assert!(!dir.join("uninstall.exe").exists());
+        assert!(!dir.join("other").exists());
+        assert_eq!(done, [1, 2, 3]);
+
+        let list_path = dir.join(BUNDLED_FILES_LIST);
+        assert_eq!(
+            listed_bundled_files(&dir, &list_path),
+            [dir.join("readme.txt"), dir.join("lib").join("data.bin")]
+        );
+
+        // Undoing it leaves the folder as it was
+        remove_extracted_files(written, dir.clone())().unwrap();
+        assert_eq!(file_names(&dir), Vec::<String>::new());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_an_exe_that_isnt_a_pe() {
+        let mut truncated = fake_exe();
+        truncated.truncate(0x82);
+        let mut no_signature = fake_exe();
+        no_signature[0x80..0x84].copy_from_slice(b"\0\0\0\0");
+
+        for (i, exe) in [
+            Vec::new(),
+            b"MZ".to_vec(),
+            truncated,
+            no_signature,
+            b"<html>".repeat(100),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let dir = temp_dir(&format!("not-a-pe-{}", i));
+            let exe_path = dir.join("TinyWiiBackupManager.exe");
+            let mut archive = zip_of(&[("TinyWiiBackupManager.exe", &exe)]);
+
+            let res = run_action(
+                &InstallAction::ExtractFiles(exe_path.clone()),
+                &mut archive,
+                "1.0.0",
+                &dir,
+                &mut |_| ControlFlow::Continue(()),
+            );
+            assert!(matches!(res, Err(InstallError::Unzip(_))));
+            assert!(!exe_path.exists());
+
+            fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn accepts_a_pe() {
+        let dir = temp_dir("pe");
+        let exe_path = dir.join("TinyWiiBackupManager.exe");
+        fs::write(&exe_path, fake_exe()).unwrap();
+        assert!(looks_like_pe(&exe_path));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn os_from_representative_build_numbers() {
+        for (build_number, os) in [
+            (7601, Os::Windows7),   // 7 SP1
+            (9200, Os::Windows7),   // 8
+            (9600, Os::Windows7),   // 8.1
+            (10240, Os::Windows),   // 10 1507
+            (19045, Os::Windows),   // 10 22H2
+            (22000, Os::Windows11), // 11 21H2
+            (26100, Os::Windows11), // 11 24H2
+        ] {
+            assert_eq!(os_from_build_number(build_number), os, "{}", build_number);
+        }
+    }
+
+    #[test]
+    fn missing_product_name_assumes_windows_10() {
+        // ERROR_FILE_NOT_FOUND, what a missing key or value reads as
+        let missing = windows_result::Error::from_hresult(windows_result::HRESULT::from_win32(2));
+        assert_eq!(os_from_registry(|| Err(missing)).ok(), Some(Os::Windows));
+    }
+
+    #[test]
+    fn os_from_product_names() {
+        for (product_name, os) in [
+            ("Windows 7 Professional", Some(Os::Windows7)),
+            ("Windows 8.1 Pro", Some(Os::Windows7)),
+            ("Windows 10 Pro", Some(Os::Windows)),
+            ("Windows Server 2022 Datacenter", Some(Os::Windows)),
+            ("", Some(Os::Windows)),
+            ("Windows Vista (TM) Business", None),
+            ("Microsoft Windows XP", None),
+        ] {
+            assert_eq!(
+                os_from_registry(|| Ok(product_name.to_string())).ok(),
+                os,
+                "{}",
+                product_name
+            );
+        }
+    }
+
+    #[test]
+    fn arch_compatibility_matrix() {
+        use Arch::*;
+
+        // Each build, and whether it runs on each detected processor in Arch::ALL order
+        let matrix = [
+            (I686, [true, true, true, true]),
+            (X86_64, [false, true, true, true]),
+            (X86_64v3, [false, false, true, false]),
+            (Aarch64, [false, false, false, true]),
+        ];
+        for (build, runs_on) in matrix {
+            for (detected, compatible) in Arch::ALL.into_iter().zip(runs_on) {
+                assert_eq!(
+                    build.is_compatible_with(detected),
+                    compatible,
+                    "{:?} on {:?}",
+                    build,
+                    detected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn arch_from_native_machine_wins_over_the_env() {
+        // An x86 installer emulated on ARM64 sees x86 in both variables
+        assert_eq!(
+            arch_from(Some(IMAGE_FILE_MACHINE_ARM64), None, Some("x86")),
+            Arch::Aarch64
+        );
+        assert_eq!(
+            arch_from(Some(IMAGE_FILE_MACHINE_AMD64), Some("AMD64"), Some("x86")),
+            Arch::X86_64
+        );
+        assert_eq!(
+            arch_from(Some(IMAGE_FILE_MACHINE_I386), None, Some("x86")),
+            Arch::I686
+        );
+    }
+
+    #[test]
+    fn arch_from_the_env_without_a_native_machine() {
+        for (processor_architew6432, processor_architecture, arch) in [
+            (Some("AMD64"), Some("x86"), Arch::X86_64), // WOW64
+            (Some("ARM64"), Some("x86"), Arch::Aarch64),
+            (None, Some("AMD64"), Arch::X86_64), // Native
+            (None, Some("arm64"), Arch::Aarch64),
+            (None, Some("x86"), Arch::I686),
+            (None, Some("IA64"), Arch::I686),
+            (None, None, Arch::I686),
+        ] {
+            assert_eq!(
+                arch_from(None, processor_architew6432, processor_architecture),
+                arch,
+                "{:?} {:?}",
+                processor_architew6432,
+                processor_architecture
+            );
+        }
+    }
+
+    #[test]
+    fn version_file_with_crlf() {
+        assert_eq!(parse_version_file("1.2.3\r\n").unwrap(), "1.2.3");
+    }
+
+    #[test]
+    fn version_file_with_a_bom() {
+        assert_eq!(parse_version_file("\u{feff}1.2.3\n").unwrap(), "1.2.3");
+    }
+
+    #[test]
+    fn version_file_thats_a_web_page() {
+        let error = parse_version_file("<html><body>Sign in to continue</body></html>")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("web page"), "{}", error);
+    }
+
+    /// A release mirror serving `version_txt` and an API whose latest release is `latest_tag`.
+    /// Either one is a 404 when it's None.
+    fn latest_version_from(
+        version_txt: Option<&'static str>,
+        latest_tag: Option<&'static str>,
+    ) -> Result<String> {
+        let base_url = serve(move |path| match (path, version_txt, latest_tag) {
+            ("/releases/latest/download/version.txt", Some(version_txt), _) => {
+                http_response("200 OK", &[], version_txt.as_bytes())
+            }
+            ("/api/releases/latest", _, Some(tag)) => http_response(
+                "200 OK",
+                &[("Content-Type", "application/json")],
+                format!("{{\"tag_name\":\"{}\"}}", tag).as_bytes(),
+            ),
+            _ => http_response("404 Not Found", &[], b"Not Found"),
+        });
+
+        latest_version(&base_url, &format!("{}/api/releases/latest", base_url))
+    }
+
+    #[test]
+    fn latest_version_from_version_txt() {
+        assert_eq!(
+            latest_version_from(Some("1.2.3\r\n"), Some("v1.0.0")).unwrap(),
+            "1.2.3"
+        );
+    }
+
+    #[test]
+    fn latest_version_falls_back_to_the_api() {
+        // version.txt missing from the release, or something else served in its place
+        assert_eq!(latest_version_from(None, Some("v1.2.4")).unwrap(), "1.2.4");
+        assert_eq!(
+            latest_version_from(Some("<html></html>"), Some("v1.2.4")).unwrap(),
+            "1.2.4"
+        );
+    }
+
+    #[test]
+    fn latest_version_reports_the_version_txt_error() {
+        let error = latest_version_from(None, None).unwrap_err().to_string();
+        assert!(error.contains("version.txt (HTTP 404)"), "{}", error);
+    }
+
+    #[test]
+    fn is_newer_compares_numerically() {
+        assert!(is_newer("1.10.0", "1.9.0"));
+        assert!(!is_newer("1.9.0", "1.10.0"));
+        assert!(is_newer("2.0", "1.99.99"));
+        assert!(is_newer("v1.2.4", "1.2.3"));
+    }
+
+    #[test]
+    fn is_newer_equal_versions() {
+        assert!(!is_newer("1.2.3", "1.2.3"));
+        assert!(!is_newer("1.2.3", "v1.2.3"));
+        // Missing components count as 0
+        assert!(!is_newer("1.2", "1.2.0"));
+        assert!(!is_newer("1.2.0", "1.2"));
+    }
+
+    #[test]
+    fn is_newer_pre_releases() {
+        assert!(is_newer("1.0.0", "1.0.0-beta.4"));
+        assert!(!is_newer("1.0.0-beta.4", "1.0.0"));
+        assert!(is_newer("1.0.1-beta.1", "1.0.0"));
+    }
+
+    #[test]
+    fn malformed_versions_are_never_newer() {
+        for (latest, installed) in [
+            ("", "1.0.0"),
+            ("1.0.0", ""),
+            ("latest", "1.0.0"),
+            ("1.x.0", "1.0.0"),
+            ("1..0", "1.0.0"),
+            ("<html>", "1.0.0"),
+        ] {
+            assert!(!is_newer(latest, installed), "{} vs {}", latest, installed);
+        }
+    }
+
+    /// Answers every request to a local port with `respond(path)`, a raw HTTP response,
+    /// one connection at a time. Returns the base URL.
+    fn serve(respond: impl Fn(&str) -> Vec<u8> + Send + 'static) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|end| end == b"\r\n\r\n") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let request = String::from_utf8_lossy(&request);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let _ = stream.write_all(&respond(path));
+            }
+        });
+
+        base_url
+    }
+
+    fn http_response(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            status,
+            body.len()
+        );
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str("\r\n");
+
+        let mut response = response.into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    /// Passes for a release zip as far as the download is concerned
+    fn fake_zip_body(len: usize) -> Vec<u8> {
+        let mut body = b"PK\x03\x04".to_vec();
+        body.extend((0..len - 4).map(|i| (i % 251) as u8));
+        body
+    }
+
+    #[test]
+    fn hashes_the_download_as_it_comes_in() {
+        let body = fake_zip_body(8 * 1024 * 1024);
+        let served = body.clone();
+        let base_url = serve(move |_| http_response("200 OK", &[], &served));
+        let url = format!("{}/hash-while-downloading.zip", base_url);
+
+        let (bytes, hash) = download_zip(&url, None, |_| ControlFlow::Continue(())).unwrap();
+
+        assert_eq!(bytes, body);
+        // The same as hashing it in a second pass once the download was done
+        assert_eq!(hash, sha256_hex(&bytes));
+    }
+
+    #[test]
+    fn partial_downloads_dont_share_a_file() {
+        let artifact = |id| {
+            partial_download_path(&format!(
+                "https://api.github.com/repos/mq1/TinyWiiBackupManager/actions/artifacts/{}/zip",
+                id
+            ))
+        };
+        assert_ne!(artifact(1), artifact(2));
+        assert_eq!(artifact(1), artifact(1));
+
+        let release = partial_download_path(&asset_url(
+            DEFAULT_BASE_URL,
+            "1.2.3",
+            "TinyWiiBackupManager-v1.2.3-windows-x86_64.zip",
+        ));
+        let file_name = release.file_name().unwrap().to_string_lossy();
+        assert!(file_name.starts_with("TinyWiiBackupManager-v1.2.3-windows-x86_64.zip-"));
+        assert!(file_name.ends_with(".part"));
+    }
+
+    #[test]
+    fn download_size_bounds() {
+        let limit = max_download_mb() * 1024 * 1024;
+
+        assert!(check_download_size(MIN_DOWNLOAD_SIZE).is_ok());
+        assert!(check_download_size(limit).is_ok());
+        assert!(matches!(
+            check_download_size(MIN_DOWNLOAD_SIZE - 1),
+            Err(InstallError::DownloadTooSmall(_))
+        ));
+        assert!(matches!(
+            check_download_size(limit + 1),
+            Err(InstallError::DownloadTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn short_download_is_refused() {
+        // About the size of an error page
+        let base_url = serve(|_| http_response("200 OK", &[], &fake_zip_body(2048)));
+        let url = format!("{}/short.zip", base_url);
+
+        let res = download_zip(&url, None, |_| ControlFlow::Continue(()));
+        assert!(matches!(res, Err(InstallError::DownloadTooSmall(2048))));
+    }
+
+    #[test]
+    fn oversized_download_is_refused_before_reading_it() {
+        // Only the headers, it has to be turned down on the Content-Length alone
+        let size = max_download_mb() * 1024 * 1024 + 1;
+        let base_url = serve(move |_| {
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                size
+            )
+            .into_bytes()
+        });
+        let url = format!("{}/oversized.zip", base_url);
+
+        let res = download_zip(&url, None, |_| ControlFlow::Continue(()));
+        assert!(matches!(
+            res,
+            Err(InstallError::DownloadTooLarge { size: s, .. }) if s == size
+        ));
+    }
+
+    #[test]
+    fn download_that_isnt_a_zip_is_refused() {
+        let mut body = fake_zip_body(256 * 1024);
+        body[..4].copy_from_slice(b"MZ\x90\x00");
+        let base_url = serve(move |_| http_response("200 OK", &[], &body));
+        let url = format!("{}/not-a-zip.zip", base_url);
+
+        let res = download_zip(&url, None, |_| ControlFlow::Continue(()));
+        assert!(matches!(res, Err(InstallError::NotAZip)));
+    }
+
+    /// Serves one download, the headers after `first` and a 16 KiB chunk `every` after that.
+    /// Sends when it noticed the client hung up, if it did.
+    fn trickle(
+        first: Duration,
+        every: Duration,
+        body: Vec<u8>,
+    ) -> (String, mpsc::Receiver<Instant>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, hung_up) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            thread::sleep(first);
+            let headers = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(headers.as_bytes());
+            for chunk in body.chunks(16 * 1024) {
+                thread::sleep(every);
+                if stream.write_all(chunk).is_err() {
+                    let _ = tx.send(Instant::now());
+                    return;
+                }
+            }
+        });
+
+        (base_url, hung_up)
+    }
+
+    #[test]
+    fn racing_hangs_up_on_the_slower_source() {
+        let body = fake_zip_body(256 * 1024);
+        // The whole body takes about 1.6 seconds, the other source answers after 0.3
+        let every = Duration::from_millis(100);
+        let (fast, _) = trickle(Duration::ZERO, every, body.clone());
+        let (slow, slow_hung_up) = trickle(Duration::from_millis(300), every, body.clone());
+        let name = "racing-test.zip";
+
+        let (bytes, _) =
+            download_racing("1.0.0", name, &[slow, fast], |_| ControlFlow::Continue(())).unwrap();
+        let finished = Instant::now();
+
+        assert_eq!(bytes, body);
+        // Not left open until the download from the other one was done
+        let hung_up = slow_hung_up.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(hung_up < finished);
+    }
+
+    fn redirect(location: &str) -> Vec<u8> {
+        http_response("302 Found", &[("Location", location)], b"")
+    }
+
+    #[test]
+    fn follows_redirects_to_the_asset() {
+        let body = fake_zip_body(256 * 1024);
+        let served = body.clone();
+        // Like GitHub, which sends assets through its CDN, plus a mirror's hop
+        let base_url = serve(move |path| match path {
+            "/releases/redirected.zip" => redirect("/mirror/redirected.zip"),
+            "/mirror/redirected.zip" => redirect("/cdn/redirected.zip"),
+            "/cdn/redirected.zip" => {
+                http_response("200 OK", &[("Content-Type", "application/zip")], &served)
+            }
+            _ => http_response("404 Not Found", &[], b""),
+        });
+        let url = format!("{}/releases/redirected.zip", base_url);
+
+        let (bytes, _) = download_zip(&url, None, |_| ControlFlow::Continue(())).unwrap();
+        assert_eq!(bytes, body);
+    }
+
+    #[test]
+    fn redirect_loop_is_a_readable_error() {
+        let base_url = serve(|path| match path {
+            "/a/loop.zip" => redirect("/b/loop.zip"),
+            _ => redirect("/a/loop.zip"),
+        });
+        let url = format!("{}/a/loop.zip", base_url);
+
+        let Err(InstallError::Network(error)) =
+            download_zip(&url, None, |_| ControlFlow::Continue(()))
+        else {
+            panic!("expected a network error");
+        };
+        assert!(error.contains("redirected too many times"), "{}", error);
+    }
+
+    #[test]
+    fn web_page_instead_of_the_download() {
+        let base_url = serve(|path| match path {
+            "/portal.zip" => redirect("/login"),
+            _ => http_response(
+                "200 OK",
+                &[("Content-Type", "text/html; charset=utf-8")],
+                b"<html><body>Sign in to continue</body></html>",
+            ),
+        });
+        let url = format!("{}/portal.zip", base_url);
+
+        let Err(InstallError::Network(error)) =
+            download_zip(&url, None, |_| ControlFlow::Continue(()))
+        else {
+            panic!("expected a network error");
+        };
+        assert!(error.contains("web page"), "{}", error);
+    }
+
+    #[test]
+    fn throttled_stream_keeps_to_the_rate() {
+        let rate = 200 * 1024;
+        let mut stream = Cursor::new(vec![0; 400 * 1024]);
+        let mut buf = vec![0; 64 * 1024];
+
+        let throttle = Throttle::new(rate, 0);
+        let chunk_len = throttle.chunk_len(buf.len());
+        assert_eq!(chunk_len, 20 * 1024);
+
+        let mut len = 0;
+        loop {
+            let n = stream.read(&mut buf[..chunk_len]).unwrap();
+            if n == 0 {
+                break;
+            }
+            len += n;
+            throttle.pace(len);
+        }
+
+        // 2 seconds' worth, no faster and with some leeway for a busy machine
+        let actual = len as f64 / throttle.started.elapsed().as_secs_f64();
+        assert!(actual <= rate as f64 * 1.01, "{} B/s", actual);
+        assert!(actual >= rate as f64 * 0.8, "{} B/s", actual);
+    }
+
+    #[test]
+    fn throttle_chunks_stay_in_bounds() {
+        assert_eq!(Throttle::new(1024, 0).chunk_len(64 * 1024), 1024);
+        assert_eq!(
+            Throttle::new(100 * 1024 * 1024, 0).chunk_len(64 * 1024),
+            64 * 1024
+        );
+    }
+
+    /// Hands out `chunks` full buffers, waiting `delay` before each one
+    struct SlowReader {
+        chunks: usize,
+        delay: Duration,
+    }
+
+    impl Read for SlowReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.chunks == 0 {
+                return Ok(0);
+            }
+
+            thread::sleep(self.delay);
+            self.chunks -= 1;
+            buf.fill(1);
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn slow_download_outlasts_the_idle_timeout() {
+        let reader = SlowReader {
+            chunks: 8,
+            delay: Duration::from_millis(150),
+        };
+        let chunks = read_chunks(reader, 1024);
+
+        // About 1.2 seconds in all, but never more than 150 ms without a chunk
+        let mut len = 0;
+        loop {
+            let chunk = next_chunk(&chunks, Duration::from_millis(500)).unwrap();
+            if chunk.is_empty() {
+                break;
+            }
+            len += chunk.len();
+        }
+        assert_eq!(len, 8 * 1024);
+    }
+
+    #[test]
+    fn stalled_download_times_out() {
+        let reader = SlowReader {
+            chunks: 1,
+            delay: Duration::from_secs(5),
+        };
+        let chunks = read_chunks(reader, 1024);
+
+        let started = Instant::now();
+        let Err(InstallError::Network(error)) = next_chunk(&chunks, Duration::from_millis(200))
+        else {
+            panic!("expected a network error");
+        };
+        assert!(error.contains("timed out"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn dead_connection_times_out() {
+        // Connections are queued without accepting them, and nothing is ever answered
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/version.txt", listener.local_addr().unwrap());
+
+        let started = Instant::now();
+        let res = request(minreq::Method::Get, &url, Some(1))
+            .unwrap()
+            .send()
+            .map_err(network_error);
+        assert!(matches!(res, Err(InstallError::Network(_))));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    /// Shortcuts where an install of `dir` would put them, under `dir` itself
+    fn shortcut_layout(dir: &Path) -> [PathBuf; 3] {
+        let paths = [
+            dir.join("Desktop").join(LNK),
+            dir.join("Start Menu")
+                .join("TinyWiiBackupManager")
+                .join(LNK),
+            dir.join("TaskBar").join(LNK),
+        ];
+        for path in &paths {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, b"lnk").unwrap();
+        }
+
+        paths
+    }
+
+    #[test]
+    fn uninstall_removes_the_shortcuts_and_their_folder() {
+        let dir = temp_dir("uninstall-shortcuts");
+        let [desktop, start_menu, taskbar] = shortcut_layout(&dir);
+
+        remove_shortcuts(&desktop, &start_menu, &taskbar).unwrap();
+
+        assert!(!desktop.exists());
+        assert!(!start_menu.parent().unwrap().exists());
+        assert!(!taskbar.exists());
+        // Only the folders that were already there are left
+        assert_eq!(file_names(&dir), ["Desktop", "Start Menu", "TaskBar"]);
+
+        // Nothing left to remove is fine too
+        remove_shortcuts(&desktop, &start_menu, &taskbar).unwrap();
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn other_start_menu_shortcuts_survive() {
+        let dir = previous_install("start-menu-extra");
+        let start_menu = dir.join("Start Menu");
+        fs::create_dir(&start_menu).unwrap();
+        fs::write(start_menu.join(LNK), b"old lnk").unwrap();
+        fs::write(start_menu.join("Dolphin.lnk"), b"user lnk").unwrap();
+
+        run_actions(
+            &file_steps(&dir),
+            &mut release_zip(),
+            "1.0.0",
+            &dir,
+            &mut |_| ControlFlow::Continue(()),
+        )
+        .unwrap();
+        assert_eq!(file_names(&start_menu), ["Dolphin.lnk", LNK]);
+        assert_ne!(fs::read(start_menu.join(LNK)).unwrap(), b"old lnk");
+
+        remove_shortcuts(&dir.join(LNK), &start_menu.join(LNK), &dir.join("pin.lnk")).unwrap();
+        assert_eq!(file_names(&start_menu), ["Dolphin.lnk"]);
+        assert_eq!(
+            fs::read(start_menu.join("Dolphin.lnk")).unwrap(),
+            b"user lnk"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn uninstall_removes_the_install_dir() {
+        let dir = previous_install("uninstall-dir");
+        let install_dir = dir.join("TinyWiiBackupManager");
+        fs::create_dir(&install_dir).unwrap();
+        fs::write(install_dir.join("TinyWiiBackupManager.exe"), fake_exe()).unwrap();
+        fs::create_dir_all(install_dir.join("lib")).unwrap();
+        fs::write(install_dir.join("lib").join("data.bin"), b"data").unwrap();
+        fs::write(install_dir.join("config.toml"), b"user data").unwrap();
+
+        remove_install_dir(&install_dir, None, false).unwrap();
+        assert!(!install_dir.exists());
+
+        // Already gone
+        remove_install_dir(&install_dir, None, false).unwrap();
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn uninstall_keeps_user_data_when_asked() {
+        let dir = previous_install("uninstall-keep");
+        fs::create_dir(dir.join("lib")).unwrap();
+        fs::write(dir.join("lib").join("data.bin"), b"data").unwrap();
+        fs::write(dir.join(BUNDLED_FILES_LIST), "lib\\data.bin").unwrap();
+        fs::write(dir.join("config.toml"), b"user data").unwrap();
+
+        remove_install_dir(&dir, None, true).unwrap();
+
+        assert_eq!(file_names(&dir), ["config.toml"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn uninstall_skips_the_running_uninstaller() {
+        let dir = previous_install("uninstall-skip");
+        let uninstaller = dir.join("uninstall.exe");
+
+        remove_install_dir(&dir, Some(&uninstaller), false).unwrap();
+
+        assert_eq!(file_names(&dir), ["uninstall.exe"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn rate_limited_only_with_no_requests_left() {
+        let exhausted = headers(&[("x-ratelimit-remaining", "0")]);
+        assert!(is_rate_limited(403, &exhausted));
+        assert!(is_rate_limited(429, &exhausted));
+        assert!(!is_rate_limited(200, &exhausted));
+
+        // A 403 for some other reason, like a blocked repo
+        assert!(!is_rate_limited(
+            403,
+            &headers(&[("x-ratelimit-remaining", "42")])
+        ));
+        assert!(!is_rate_limited(403, &headers(&[])));
+    }
+
+    #[test]
+    fn rate_limit_reset_in_minutes() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let reset = |at: u64| headers(&[("x-ratelimit-reset", &at.to_string())]);
+
+        assert_eq!(rate_limit_reset_minutes(&reset(now + 125)), Some(3));
+        // Already reset, or a clock that's ahead
+        assert_eq!(rate_limit_reset_minutes(&reset(now - 60)), Some(0));
+        assert_eq!(rate_limit_reset_minutes(&headers(&[])), None);
+        assert_eq!(
+            rate_limit_reset_minutes(&headers(&[("x-ratelimit-reset", "soon")])),
+            None
+        );
+    }
+
+    /// What every non-portable plan starts with, for `dir`
+    fn extract_steps(dir: &Path) -> Vec<InstallAction> {
+        vec![
+            InstallAction::RemoveManagedFiles(dir.to_path_buf()),
+            InstallAction::ExtractFiles(dir.join("TinyWiiBackupManager.exe")),
+            InstallAction::CopyUninstaller(dir.join("uninstall.exe")),
+        ]
+    }
+
+    #[test]
+    fn plan_for_default_options() {
+        let dir = env::temp_dir().join("TinyWiiBackupManager");
+        let mut expected = extract_steps(&dir);
+        expected.extend([
+            InstallAction::CreateDesktopShortcut(
+                desktop_shortcut_path(Scope::CurrentUser).unwrap(),
+            ),
+            InstallAction::CreateStartMenuShortcut(
+                start_menu_shortcut_path(Scope::CurrentUser).unwrap(),
+            ),
+            InstallAction::WriteUninstallKeys(Scope::CurrentUser),
+        ]);
+
+        assert_eq!(
+            plan_install(&dir, InstallOptions::default()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn plan_for_all_users_with_every_extra() {
+        let dir = env::temp_dir().join("TinyWiiBackupManager");
+        let options = InstallOptions {
+            pin_to_taskbar: true,
+            associate_files: true,
+            associate_iso: true,
+            add_to_path: true,
+            create_restore_point: true,
+            run_on_startup: true,
+            scope: Scope::AllUsers,
+            ..InstallOptions::default()
+        };
+        let start_menu_shortcut = start_menu_shortcut_path(Scope::AllUsers).unwrap();
+        let mut expected = vec![InstallAction::CreateRestorePoint];
+        expected.extend(extract_steps(&dir));
+        expected.extend([
+            InstallAction::CreateDesktopShortcut(desktop_shortcut_path(Scope::AllUsers).unwrap()),
+            InstallAction::CreateStartMenuShortcut(start_menu_shortcut.clone()),
+            InstallAction::PinToTaskbar(start_menu_shortcut),
+            InstallAction::AddToPath(dir.clone(), Scope::AllUsers),
+            InstallAction::RegisterFileAssociations {
+                include_iso: true,
+                scope: Scope::AllUsers,
+            },
+            InstallAction::RunOnStartup,
+            InstallAction::WriteUninstallKeys(Scope::AllUsers),
+        ]);
+
+        assert_eq!(plan_install(&dir, options).unwrap(), expected);
+    }
+
+    #[test]
+    fn plan_without_shortcuts_doesnt_pin() {
+        let dir = env::temp_dir().join("TinyWiiBackupManager");
+        let options = InstallOptions {
+            desktop_shortcut: false,
+            start_menu_shortcut: false,
+            pin_to_taskbar: true,
+            ..InstallOptions::default()
+        };
+        let mut expected = extract_steps(&dir);
+        expected.push(InstallAction::WriteUninstallKeys(Scope::CurrentUser));
+
+        assert_eq!(plan_install(&dir, options).unwrap(), expected);
+    }
+
+    #[test]
+    fn portable_plan_ignores_the_other_options() {
+        let dir = env::temp_dir().join("TinyWiiBackupManager");
+        let options = InstallOptions {
+            portable: true,
+            add_to_path: true,
+            create_restore_point: true,
+            run_on_startup: true,
+            scope: Scope::AllUsers,
+            ..InstallOptions::default()
+        };
+        let mut expected = extract_steps(&dir);
+        expected.push(InstallAction::WritePortableMarker(
+            dir.join(PORTABLE_MARKER),
+        ));
+
+        assert_eq!(plan_install(&dir, options).unwrap(), expected);
+    }
+
+    #[test]
+    fn asset_url_format() {
+        assert_eq!(
+            asset_url(
+                DEFAULT_BASE_URL,
+                "1.2.3",
+                "TinyWiiBackupManager-v1.2.3-windows-x86_64.zip"
+            ),
+            "https://github.com/mq1/TinyWiiBackupManager/releases/download/v1.2.3/TinyWiiBackupManager-v1.2.3-windows-x86_64.zip"
+        );
+    }
+
+    #[test]
+    fn download_url_uses_the_current_asset_name() {
+        assert_eq!(
+            download_url("4.5.6", Os::Windows7, Arch::I686),
+            format!(
+                "{}/releases/download/v4.5.6/TinyWiiBackupManager-v4.5.6-windows7-x86.zip",
+                base_url()
+            )
+        );
+        assert!(
+            download_url("4.5.6", Os::Windows11, Arch::X86_64v3)
+                .ends_with("/v4.5.6/TinyWiiBackupManager-v4.5.6-windows-x86_64-v3.zip")
+        );
+    }
+
+    #[test]
+    fn asset_name_candidates_current_scheme_first() {
+        assert_eq!(
+            asset_name_candidates("1.2.3", Os::Windows, Arch::X86_64),
+            ["TinyWiiBackupManager-v1.2.3-windows-x86_64.zip"]
+        );
+        assert_eq!(
+            asset_name_candidates("1.2.3", Os::Windows7, Arch::X86_64v3),
+            ["TinyWiiBackupManager-v1.2.3-windows7-x86_64-v3.zip"]
+        );
+    }
+
+    #[test]
+    fn version_from_every_candidate_name() {
+        for version in ["1.2.3", "1.0.0-beta.4"] {
+            for os in Os::ALL {
+                for arch in Arch::ALL {
+                    for name in asset_name_candidates(version, os, arch) {
+                        assert_eq!(
+                            version_from_zip_name(Path::new(&name)).unwrap(),
+                            version,
+                            "{}",
+                            name
+                        );
+                    }
+                }
+            }
+        }
+
+        // As the browser names a second download
+        assert_eq!(
+            version_from_zip_name(Path::new(
+                "TinyWiiBackupManager-v1.2.3-windows-x86_64 (1).zip"
+            ))
+            .unwrap(),
+            "1.2.3"
+        );
+        assert!(version_from_zip_name(Path::new("TinyWiiBackupManager.zip")).is_err());
+        assert!(version_from_zip_name(Path::new("TinyWiiBackupManager-vX-windows.zip")).is_err());
+    }
+
+    /// The SHA-256 of "abc", from FIPS 180-2
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    fn sha256_hex(bytes: &[u8]) -> String {
+        to_hex(&Sha256::digest(bytes))
+    }
+
+    #[test]
+    fn checksum_matches_known_vector() {
+        let hash = sha256_hex(b"abc");
+        assert_eq!(hash, ABC_SHA256);
+        assert!(verify_checksum(&hash, ABC_SHA256).is_ok());
+        // As a sidecar may have it
+        assert!(verify_checksum(&hash, &format!("{}\n", ABC_SHA256.to_ascii_uppercase())).is_ok());
+    }
+
+    #[test]
+    fn checksum_rejects_tampered_bytes() {
+        let hash = sha256_hex(b"abd");
+        assert!(matches!(
+            verify_checksum(&hash, ABC_SHA256),
+            Err(InstallError::Checksum { .. })
+        ));
+
+        // A truncated download
+        let hash = sha256_hex(b"ab");
+        assert!(verify_checksum(&hash, ABC_SHA256).is_err());
+    }
+
+    const LNK: &str = "TinyWiiBackupManager.lnk";
+
+    /// The steps of an install to `dir` that stay in it, none of them touch the registry
+    fn file_steps(dir: &Path) -> [InstallAction; 5] {
+        [
+            InstallAction::RemoveManagedFiles(dir.to_path_buf()),
+            InstallAction::ExtractFiles(dir.join("TinyWiiBackupManager.exe")),
+            InstallAction::CopyUninstaller(dir.join("uninstall.exe")),
+            InstallAction::CreateDesktopShortcut(dir.join(LNK)),
+            InstallAction::CreateStartMenuShortcut(dir.join("Start Menu").join(LNK)),
+        ]
+    }
+
+    #[test]
+    fn failed_step_restores_previous_install() {
+        // The same steps, pointed somewhere they can't write to
+        let failing = |dir: &Path| {
+            let file = dir.join("TinyWiiBackupManager.exe");
+            let missing = dir.join("missing");
+            [
+                InstallAction::RemoveManagedFiles(file.clone()),
+                InstallAction::ExtractFiles(missing.join("TinyWiiBackupManager.exe")),
+                InstallAction::CopyUninstaller(missing.join("uninstall.exe")),
+                InstallAction::CreateDesktopShortcut(missing.join(LNK)),
+                InstallAction::CreateStartMenuShortcut(file.join(LNK)),
+            ]
+        };
+
+        for i in 0..file_steps(Path::new("")).len() {
+            let dir = previous_install(&format!("rollback-{}", i));
+            let mut actions = file_steps(&dir)[..i].to_vec();
+            actions.push(failing(&dir)[i].clone());
+
+            run_until_failure(&actions, &mut release_zip(), &dir, &mut |_| {
+                ControlFlow::Continue(())
+            });
+            assert_previous_install(&dir);
+
+            fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn reports_each_kind_of_step_once_in_order() {
+        let dir = previous_install("progress");
+        let mut events = Vec::new();
+
+        let (done, warnings) = run_actions(
+            &file_steps(&dir),
+            &mut release_zip(),
+            "1.0.0",
+            &dir,
+            &mut |event| {
+                events.push(event);
+                ControlFlow::Continue(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            events,
+            [
+                ProgressEvent::Extracting,
+                ProgressEvent::ExtractedFile { done: 1, total: 2 },
+                ProgressEvent::ExtractedFile { done: 2, total: 2 },
+                ProgressEvent::CreatingShortcuts,
+            ]
+        );
+        assert_eq!(done.len(), 5);
+        assert_eq!(warnings, Vec::<String>::new());
+        assert!(dir.join("Start Menu").join(LNK).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// A dir under `dir` about `len` characters long
+    fn nested_dir(dir: &Path, len: usize) -> PathBuf {
+        let mut nested = dir.to_path_buf();
+        while nested.as_os_str().len() < len {
+            nested.push("nested");
+        }
+
+        nested
+    }
+
+    #[test]
+    fn installs_into_a_deeply_nested_dir() {
+        let dir = temp_dir("nested");
+        // Just short enough for the Start menu shortcut and the .old backups
+        let nested = nested_dir(&dir, 190);
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(
+            install_dir_in(nested.clone()).unwrap(),
+            nested.join("TinyWiiBackupManager")
+        );
+
+        let (done, warnings) = run_actions(
+            &file_steps(&nested),
+            &mut release_zip(),
+            "1.0.0",
+            &nested,
+            &mut |_| ControlFlow::Continue(()),
+        )
+        .unwrap();
+        assert_eq!(done.len(), 5);
+        assert_eq!(warnings, Vec::<String>::new());
+        assert!(nested.join("lib").join("readme.txt").exists());
+        assert!(nested.join(LNK).exists());
+        assert!(nested.join("Start Menu").join(LNK).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_a_dir_too_long_for_max_path() {
+        let nested = nested_dir(&env::temp_dir(), MAX_PATH);
+
+        let error = install_dir_in(nested.clone()).unwrap_err().to_string();
+        assert!(error.contains("too long"), "{}", error);
+        assert!(plan_install(&nested, InstallOptions::default()).is_err());
+    }
+
+    #[test]
+    fn install_dir_is_normalized() {
+        let dir = env::temp_dir();
+        let expected = dir.join("TinyWiiBackupManager");
+
+        let prefixed = PathBuf::from(format!(r"\\?\{}", dir.display()));
+        assert_eq!(install_dir_in(prefixed).unwrap(), expected);
+        assert_eq!(install_dir_in(dir.join("a").join("..")).unwrap(), expected);
+        assert_eq!(install_dir_in(expected.clone()).unwrap(), expected);
+    }
+
+    #[test]
+    fn wide_path_prefixes_only_long_paths() {
+        let short = Path::new(r"C:\Games");
+        assert_eq!(wide_path(short), wide(r"C:\Games"));
+
+        let long = nested_dir(Path::new(r"C:\"), MAX_PATH);
+        assert_eq!(wide_path(&long), wide(&format!(r"\\?\{}", long.display())));
+
+        let unc = nested_dir(Path::new(r"\\server\share"), MAX_PATH);
+        let expected = format!(r"\\?\UNC{}", &unc.to_str().unwrap()[1..]);
+        assert_eq!(wide_path(&unc), wide(&expected));
+    }
+
+    #[test]
+    fn failed_shortcuts_dont_stop_the_install() {
+        let dir = previous_install("shortcut-failure");
+        let exe = dir.join("TinyWiiBackupManager.exe");
+        let [remove, extract, copy_uninstaller, ..] = file_steps(&dir);
+        let actions = [
+            remove,
+            extract,
+            // A desktop that isn't there, and a Start menu folder that can't be created
+            InstallAction::CreateDesktopShortcut(dir.join("missing").join(LNK)),
+            InstallAction::CreateStartMenuShortcut(exe.join(LNK)),
+            copy_uninstaller,
+        ];
+
+        let (done, warnings) =
+            run_actions(&actions, &mut release_zip(), "1.0.0", &dir, &mut |_| {
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+
+        assert_eq!(
+            done,
+            [actions[0].clone(), actions[1].clone(), actions[4].clone()]
+        );
+        assert_eq!(warnings.len(), 2);
+        for (warning, action) in warnings.iter().zip(&actions[2..4]) {
+            assert!(
+                warning.starts_with(&format!("\"{}\" failed", action)),
+                "{}",
+                warning
+            );
+            assert!(warning.contains("by hand"), "{}", warning);
+        }
+        assert!(looks_like_pe(&exe));
+        assert!(dir.join("uninstall.exe").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn user_files_survive_a_reinstall() {
+        let dir = previous_install("user-files");
+        fs::write(dir.join("config.toml"), b"theme = \"dark\"").unwrap();
+
+        run_actions(
+            &file_steps(&dir),
+            &mut release_zip(),
+            "1.0.0",
+            &dir,
+            &mut |_| ControlFlow::Continue(()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read(dir.join("config.toml")).unwrap(),
+            b"theme = \"dark\""
+        );
+        assert_eq!(
+            fs::read(dir.join("TinyWiiBackupManager.exe")).unwrap(),
+            fake_exe()
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn cancel_from_on_progress_rolls_back() {
+        let dir = previous_install("cancel-progress");
+        let mut events = Vec::new();
+
+        let res = run_actions(
+            &file_steps(&dir),
+            &mut release_zip(),
+            "1.0.0",
+            &dir,
+            &mut |event| {
+                events.push(event);
+                match event {
+                    ProgressEvent::CreatingShortcuts => ControlFlow::Break(()),
+                    _ => ControlFlow::Continue(()),
+                }
+            },
+        );
+
+        let Err(InstallError::RolledBack {
+            error, not_undone, ..
+        }) = res
+        else {
+            panic!("expected a rollback");
+        };
+        assert!(matches!(*error, InstallError::Cancelled));
+        assert_eq!(not_undone, Vec::<String>::new());
+        // Nothing is reported once it's cancelled
+        assert_eq!(events.last(), Some(&ProgressEvent::CreatingShortcuts));
+        assert_previous_install(&dir);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn cancelled_extraction_restores_previous_install() {
+        let dir = previous_install("cancel-extraction");
+        let actions = [
+            InstallAction::RemoveManagedFiles(dir.clone()),
+            InstallAction::ExtractFiles(dir.join("TinyWiiBackupManager.exe")),
+        ];
+
+        // Cancelled once the bundled file is out, with the exe already in place
+        let error = run_until_failure(
+            &actions,
+            &mut release_zip(),
+            &dir,
+            &mut |event| match event {
+                ProgressEvent::ExtractedFile { done: 2, .. } => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            },
+        );
+        assert!(matches!(error, InstallError::Cancelled));
+        assert_previous_install(&dir);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn prefs_survive_a_restart() {
+        let prefs = Prefs {
+            channel: Channel::Prerelease,
+            install_dir: Some(PathBuf::from("D:\\Games\\TinyWiiBackupManager")),
+            theme: ThemePreference::Dark,
+            lang: Some(Lang::Italian),
+            options: InstallOptions {
+                desktop_shortcut: false,
+                add_to_path: true,
+                ..InstallOptions::default()
+            },
+            window_position: Some((-1280, 40)),
+            window_size: Some((800, 600)),
+            skipped_version: Some("4.2.0".to_string()),
+            skip_welcome: true,
+        };
+
+        save_prefs(&prefs).unwrap();
+        assert_eq!(load_prefs(), prefs);
+
+        fs::remove_dir_all(prefs_path().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn missing_prefs_are_the_defaults() {
+        assert!(!prefs_path().unwrap().exists());
+        assert_eq!(load_prefs(), Prefs::default());
+    }
+
+    #[test]
+    fn corrupt_prefs_are_the_defaults() {
+        let path = prefs_path().unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "channel = \"prerelease\"\ntheme = [").unwrap();
+
+        assert_eq!(load_prefs(), Prefs::default());
+
+        // And saving over it starts afresh
+        update_prefs(|prefs| prefs.skip_welcome = true);
+        assert!(load_prefs().skip_welcome);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
 }